@@ -1,8 +1,12 @@
+use std::collections::HashMap;
 use std::hash;
+use std::hash::Hasher;
+use std::hash::sip::SipHasher;
 use std::fmt::{mod, Show, Formatter};
 
+use glob::Pattern;
 use semver::Version;
-use serialize::{Encoder,Encodable};
+use serialize::{Encoder,Encodable,Decoder,Decodable};
 
 use core::source::SourceId;
 use core::{
@@ -12,19 +16,48 @@ use core::{
 };
 use core::package_id::Metadata;
 use core::dependency::SerializedDependency;
-use util::{CargoResult, human};
+use util::{CargoResult, CargoError, human, lev_distance, short_hash};
 
 #[deriving(PartialEq,Clone)]
 pub struct Manifest {
     summary: Summary,
     authors: Vec<String>,
+    description: Option<String>,
     targets: Vec<Target>,
     target_dir: Path,
     doc_dir: Path,
     sources: Vec<SourceId>,
     build: Vec<String>,
-    warnings: Vec<String>,
+    warnings: Vec<ManifestWarning>,
     exclude: Vec<String>,
+    include: Vec<String>,
+    no_std: bool,
+    default_run_args: HashMap<String, Vec<String>>,
+    package_overrides: HashMap<String, Profile>,
+    profiles: HashMap<String, Profile>, // user-defined named profiles
+}
+
+/// The kinds of problems reported about a manifest, from most to least
+/// severe.
+#[deriving(Clone, PartialEq, Show)]
+pub enum WarningKind {
+    Deprecation,
+    Unused,
+    Style,
+}
+
+#[deriving(Clone, PartialEq, Show)]
+pub struct ManifestWarning {
+    pub message: String,
+    pub kind: WarningKind,
+}
+
+/// How much a package's version promises about compatibility.
+#[deriving(Clone, PartialEq, Show)]
+pub enum VersionStability {
+    PreRelease, // e.g. 1.0.0-beta, no promises at all
+    Unstable,   // 0.x, where minor releases may break
+    Stable,     // >= 1.0
 }
 
 impl Show for Manifest {
@@ -46,6 +79,9 @@ pub struct SerializedManifest {
     target_dir: String,
     doc_dir: String,
     build: Option<Vec<String>>,
+    no_std: bool,
+    default_run_args: HashMap<String, Vec<String>>,
+    profiles: HashMap<String, Profile>,
 }
 
 impl<E, S: Encoder<E>> Encodable<S, E> for Manifest {
@@ -61,32 +97,73 @@ impl<E, S: Encoder<E>> Encodable<S, E> for Manifest {
             target_dir: self.target_dir.display().to_string(),
             doc_dir: self.doc_dir.display().to_string(),
             build: if self.build.len() == 0 { None } else { Some(self.build.clone()) },
+            no_std: self.no_std,
+            default_run_args: self.default_run_args.clone(),
+            profiles: self.profiles.clone(),
         }.encode(s)
     }
 }
 
+/// One compilation in a build plan, as exported for external build tools.
+#[deriving(Show, Clone, PartialEq, Encodable)]
+pub struct BuildUnit {
+    pub name: String,
+    pub crate_types: Vec<&'static str>,
+    pub fingerprint: String,
+    pub outputs: Vec<String>,
+}
+
+static LIB_KINDS: [&'static str, ..6] = [
+    "lib", "rlib", "dylib", "cdylib", "staticlib", "proc-macro",
+];
+
 #[deriving(Show, Clone, PartialEq, Hash, Encodable)]
 pub enum LibKind {
     Lib,
     Rlib,
     Dylib,
-    StaticLib
+    Cdylib,
+    StaticLib,
+    ProcMacro
 }
 
 impl LibKind {
     pub fn from_str(string: &str) -> CargoResult<LibKind> {
-        match string {
+        let normalized: String = string.trim().chars().map(|c| {
+            c.to_lowercase()
+        }).collect();
+        match normalized.as_slice() {
             "lib" => Ok(Lib),
             "rlib" => Ok(Rlib),
             "dylib" => Ok(Dylib),
+            "cdylib" => Ok(Cdylib),
             "staticlib" => Ok(StaticLib),
-            _ => Err(human(format!("{} was not one of lib|rlib|dylib|staticlib",
-                                   string)))
+            "proc-macro" => Ok(ProcMacro),
+            _ => {
+                let closest = LIB_KINDS.iter().map(|kind| {
+                    (lev_distance(normalized.as_slice(), *kind), *kind)
+                }).min_by(|&(distance, _)| distance);
+                match closest {
+                    Some((distance, kind)) if distance <= 2 => {
+                        Err(human(format!("`{}` is not a valid crate type, \
+                                           did you mean `{}`?", string, kind)))
+                    }
+                    _ => Err(human(format!("`{}` was not one of {}", string,
+                                           LIB_KINDS.as_slice().connect("|")))),
+                }
+            }
         }
     }
 
     pub fn from_strs<S: Str>(strings: Vec<S>) -> CargoResult<Vec<LibKind>> {
-        strings.iter().map(|s| LibKind::from_str(s.as_slice())).collect()
+        let kinds: Vec<LibKind> = try!(strings.iter().map(|s| {
+            LibKind::from_str(s.as_slice())
+        }).collect());
+        if kinds.len() > 1 && kinds.contains(&ProcMacro) {
+            return Err(human("proc-macro cannot be combined with other crate \
+                              types"))
+        }
+        Ok(kinds)
     }
 
     pub fn crate_type(&self) -> &'static str {
@@ -94,7 +171,9 @@ impl LibKind {
             Lib => "lib",
             Rlib => "rlib",
             Dylib => "dylib",
-            StaticLib => "staticlib"
+            Cdylib => "cdylib",
+            StaticLib => "staticlib",
+            ProcMacro => "proc-macro"
         }
     }
 }
@@ -105,41 +184,180 @@ pub enum TargetKind {
     BinTarget
 }
 
+#[deriving(Show, Clone, PartialEq)]
+pub enum CompilerDriver {
+    Rustc,
+    Rustdoc,
+    Clippy,
+}
+
+impl CompilerDriver {
+    pub fn program(&self) -> &'static str {
+        match *self {
+            Rustc => "rustc",
+            Rustdoc => "rustdoc",
+            Clippy => "clippy-driver",
+        }
+    }
+}
+
+/// The flavor of link-time optimization a profile asks rustc for.
+#[deriving(Encodable, Decodable, Clone, PartialEq, Hash, Show)]
+pub enum Lto {
+    Off,
+    Thin,
+    Fat,
+}
+
+/// What happens when a thread panics: unwind its stack, or abort the whole
+/// process.
+#[deriving(Encodable, Decodable, Clone, PartialEq, Hash, Show)]
+pub enum PanicStrategy {
+    Unwind,
+    Abort,
+}
+
+/// How hard rustc optimizes: a numeric level from 0 to 3, or one of the
+/// levels which optimize for size (`s`) or even more aggressively for size
+/// (`z`).
+#[deriving(Clone, PartialEq, Hash)]
+pub enum OptLevel {
+    Num(u32),
+    Size,
+    SizeMin,
+}
+
+impl OptLevel {
+    pub fn from_str(string: &str) -> CargoResult<OptLevel> {
+        match string {
+            "s" => Ok(Size),
+            "z" => Ok(SizeMin),
+            _ => match from_str::<u32>(string) {
+                Some(n) if n <= 3 => Ok(Num(n)),
+                _ => Err(human(format!("`{}` is not a valid opt level, expected \
+                                        0, 1, 2, 3, s or z", string))),
+            }
+        }
+    }
+
+    pub fn is_optimized(&self) -> bool {
+        *self != Num(0)
+    }
+}
+
+impl Show for OptLevel {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Num(n) => n.fmt(f),
+            Size => "s".fmt(f),
+            SizeMin => "z".fmt(f),
+        }
+    }
+}
+
+impl<E, S: Encoder<E>> Encodable<S, E> for OptLevel {
+    fn encode(&self, s: &mut S) -> Result<(), E> {
+        self.to_string().encode(s)
+    }
+}
+
+impl<E, D: Decoder<E>> Decodable<D, E> for OptLevel {
+    fn decode(d: &mut D) -> Result<OptLevel, E> {
+        let string: String = raw_try!(Decodable::decode(d));
+        match OptLevel::from_str(string.as_slice()) {
+            Ok(level) => Ok(level),
+            Err(e) => Err(d.error(e.description().as_slice())),
+        }
+    }
+}
+
+/// Settings to layer over a `Profile` with `Profile::merge`, e.g. from the
+/// command line. Each field which is `Some` replaces the profile's value.
+#[deriving(Clone, Default, Show)]
+pub struct ProfileOverrides {
+    pub opt_level: Option<OptLevel>,
+    pub codegen_units: Option<Option<uint>>,
+    pub debug_level: Option<u32>,
+    pub debug_assertions: Option<bool>,
+    pub overflow_checks: Option<Option<bool>>,
+    pub lto: Option<Lto>,
+    pub incremental: Option<bool>,
+    pub clippy: Option<bool>,
+    pub plugin: Option<bool>,
+    pub harness: Option<bool>,
+    pub emit: Option<Vec<String>>,
+    pub cfgs: Option<Vec<String>>,
+    pub rustflags: Option<Vec<String>>,
+    pub crt_static: Option<Option<bool>>,
+    pub panic: Option<PanicStrategy>,
+    pub rpath: Option<bool>,
+    pub test_threads: Option<Option<uint>>,
+}
+
 #[deriving(Encodable, Decodable, Clone, PartialEq, Show)]
 pub struct Profile {
     env: String, // compile, test, dev, bench, etc.
-    opt_level: uint,
+    opt_level: OptLevel,
     codegen_units: Option<uint>,    // None = use rustc default
-    debug: bool,
+    debug_level: u32, // 0 = none, 1 = line tables only, 2 = full debuginfo
+    debug_assertions: bool,
+    overflow_checks: Option<bool>, // None = follow debug_assertions
+    lto: Lto,
+    incremental: bool,
+    clippy: bool, // compile with clippy-driver instead of rustc
     test: bool,
     doctest: bool,
     doc: bool,
     dest: Option<String>,
     plugin: bool,
     harness: bool, // whether to use the test harness (--test)
+    emit_metadata_only: bool, // skip codegen, as `cargo check` does
+    emit: Vec<String>, // the kinds of output rustc emits (`--emit`)
+    cfgs: Vec<String>, // extra `--cfg` flags passed to rustc
+    rustflags: Vec<String>, // extra flags passed verbatim to rustc
+    crt_static: Option<bool>, // None = use the target's default CRT linkage
+    panic: PanicStrategy,
+    rpath: bool,
+    test_threads: Option<uint>, // None = use the test runner's default
+    rustc_version: Option<String>, // None = don't track the toolchain
 }
 
 impl Profile {
     fn default() -> Profile {
         Profile {
             env: String::new(),
-            opt_level: 0,
+            opt_level: Num(0),
             codegen_units: None,
-            debug: false,
+            debug_level: 0,
+            debug_assertions: false,
+            overflow_checks: None,
+            lto: Off,
+            incremental: false,
+            clippy: false,
             test: false,
             doc: false,
             dest: None,
             plugin: false,
             doctest: false,
             harness: true,
+            emit_metadata_only: false,
+            emit: vec!["link".to_string(), "dep-info".to_string()],
+            cfgs: Vec::new(),
+            rustflags: Vec::new(),
+            crt_static: None,
+            panic: Unwind,
+            rpath: false,
+            test_threads: None,
+            rustc_version: None,
         }
     }
 
     pub fn default_dev() -> Profile {
         Profile {
             env: "compile".to_string(), // run in the default environment only
-            opt_level: 0,
-            debug: true,
+            opt_level: Num(0),
+            debug_level: 2,
+            debug_assertions: true,
             .. Profile::default()
         }
     }
@@ -147,7 +365,8 @@ impl Profile {
     pub fn default_test() -> Profile {
         Profile {
             env: "test".to_string(),
-            debug: true,
+            debug_level: 2,
+            debug_assertions: true,
             test: true,
             dest: None,
             .. Profile::default()
@@ -157,7 +376,8 @@ impl Profile {
     pub fn default_bench() -> Profile {
         Profile {
             env: "bench".to_string(),
-            opt_level: 3,
+            opt_level: Num(3),
+            debug_level: 2,
             test: true,
             dest: Some("release".to_string()),
             .. Profile::default()
@@ -167,7 +387,7 @@ impl Profile {
     pub fn default_release() -> Profile {
         Profile {
             env: "release".to_string(),
-            opt_level: 3,
+            opt_level: Num(3),
             dest: Some("release".to_string()),
             .. Profile::default()
         }
@@ -182,6 +402,111 @@ impl Profile {
         }
     }
 
+    pub fn default_check() -> Profile {
+        Profile {
+            env: "check".to_string(),
+            opt_level: Num(0),
+            debug_assertions: true,
+            emit_metadata_only: true,
+            .. Profile::default()
+        }
+    }
+
+    /// Builds as quickly as possible for quick iteration: no optimizations,
+    /// debuginfo or debug assertions, and many parallel codegen units.
+    pub fn fastest_compile() -> Profile {
+        Profile {
+            env: "compile".to_string(),
+            opt_level: Num(0),
+            codegen_units: Some(256),
+            debug_level: 0,
+            debug_assertions: false,
+            incremental: true,
+            .. Profile::default()
+        }
+    }
+
+    fn default_for_env(env: &str) -> Profile {
+        match env {
+            "compile" => Profile::default_dev(),
+            "test" => Profile::default_test(),
+            "bench" => Profile::default_bench(),
+            "release" => Profile::default_release(),
+            "doc" => Profile::default_doc(),
+            "check" => Profile::default_check(),
+            _ => Profile { env: env.to_string(), .. Profile::default() },
+        }
+    }
+
+    /// This profile with every setting present in `overrides` replaced.
+    pub fn merge(&self, overrides: &ProfileOverrides) -> Profile {
+        let o = overrides;
+        Profile {
+            opt_level: o.opt_level.unwrap_or(self.opt_level),
+            codegen_units: o.codegen_units.unwrap_or(self.codegen_units),
+            debug_level: o.debug_level.unwrap_or(self.debug_level),
+            debug_assertions: o.debug_assertions.unwrap_or(self.debug_assertions),
+            overflow_checks: o.overflow_checks.unwrap_or(self.overflow_checks),
+            lto: o.lto.unwrap_or(self.lto),
+            incremental: o.incremental.unwrap_or(self.incremental),
+            clippy: o.clippy.unwrap_or(self.clippy),
+            plugin: o.plugin.unwrap_or(self.plugin),
+            harness: o.harness.unwrap_or(self.harness),
+            emit: o.emit.clone().unwrap_or_else(|| self.emit.clone()),
+            cfgs: o.cfgs.clone().unwrap_or_else(|| self.cfgs.clone()),
+            rustflags: o.rustflags.clone().unwrap_or_else(|| self.rustflags.clone()),
+            crt_static: o.crt_static.unwrap_or(self.crt_static),
+            panic: o.panic.unwrap_or(self.panic),
+            rpath: o.rpath.unwrap_or(self.rpath),
+            test_threads: o.test_threads.unwrap_or(self.test_threads),
+            .. self.clone()
+        }
+    }
+
+    /// Returns the default profile for `env`, keeping any settings of this
+    /// profile which were changed from the defaults of its own env.
+    pub fn switch_env(&self, env: &str) -> Profile {
+        let base = Profile::default_for_env(self.env.as_slice());
+        let mut ret = Profile::default_for_env(env);
+        if self.opt_level != base.opt_level { ret.opt_level = self.opt_level; }
+        if self.codegen_units != base.codegen_units {
+            ret.codegen_units = self.codegen_units;
+        }
+        if self.debug_level != base.debug_level {
+            ret.debug_level = self.debug_level;
+        }
+        if self.debug_assertions != base.debug_assertions {
+            ret.debug_assertions = self.debug_assertions;
+        }
+        if self.overflow_checks != base.overflow_checks {
+            ret.overflow_checks = self.overflow_checks;
+        }
+        if self.emit != base.emit { ret.emit = self.emit.clone(); }
+        if self.lto != base.lto { ret.lto = self.lto; }
+        if self.incremental != base.incremental {
+            ret.incremental = self.incremental;
+        }
+        if self.clippy != base.clippy { ret.clippy = self.clippy; }
+        if self.plugin != base.plugin { ret.plugin = self.plugin; }
+        if self.harness != base.harness { ret.harness = self.harness; }
+        if self.cfgs != base.cfgs { ret.cfgs = self.cfgs.clone(); }
+        if self.rustflags != base.rustflags {
+            ret.rustflags = self.rustflags.clone();
+        }
+        if self.crt_static != base.crt_static {
+            ret.crt_static = self.crt_static;
+        }
+        if self.panic != base.panic { ret.panic = self.panic; }
+        if self.rpath != base.rpath { ret.rpath = self.rpath; }
+        if self.test_threads != base.test_threads {
+            ret.test_threads = self.test_threads;
+        }
+        if self.rustc_version != base.rustc_version {
+            ret.rustc_version = self.rustc_version.clone();
+        }
+        ret
+    }
+
     pub fn is_compile(&self) -> bool {
         self.env.as_slice() == "compile"
     }
@@ -194,6 +519,14 @@ impl Profile {
         self.test
     }
 
+    pub fn is_check(&self) -> bool {
+        self.emit_metadata_only
+    }
+
+    pub fn get_emit(&self) -> &[String] {
+        self.emit.as_slice()
+    }
+
     pub fn uses_test_harness(&self) -> bool {
         self.harness
     }
@@ -206,7 +539,198 @@ impl Profile {
         self.plugin
     }
 
-    pub fn get_opt_level(&self) -> uint {
+    /// Whether doctests built with this profile can be run on `host`. When
+    /// cross compiling to another `target` the doctests can still be
+    /// compiled, but the resulting binaries can't run on the host.
+    pub fn doctest_runnable(&self, target: &Option<String>, host: &str) -> bool {
+        self.doctest && target.as_ref().map_or(true, |t| t.as_slice() == host)
+    }
+
+    /// The `-C` codegen option to pass rustc so that debug assertions are
+    /// enabled exactly when this profile asks for them, rather than
+    /// following the opt level.
+    pub fn debug_assertions_flag(&self) -> String {
+        format!("debug-assertions={}", if self.debug_assertions {"on"} else {"off"})
+    }
+
+    /// The rustc arguments which follow from this profile's codegen
+    /// settings.
+    pub fn args_for_rustc(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.emit_metadata_only {
+            args.push("--emit=metadata".to_string());
+        } else if self.emit != Profile::default().emit {
+            // the default is left implicit, rustc emits the same on its own
+            args.push(format!("--emit={}", self.emit.connect(",")));
+        }
+        match self.opt_level {
+            Num(0) => {}
+            Num(n) => {
+                args.push("--opt-level".to_string());
+                args.push(n.to_string());
+            }
+            Size | SizeMin => {
+                args.push("-C".to_string());
+                args.push(format!("opt-level={}", self.opt_level));
+            }
+        }
+        match self.codegen_units {
+            Some(n) => {
+                args.push("-C".to_string());
+                args.push(format!("codegen-units={}", n));
+            }
+            None => {}
+        }
+        if self.debug_level == 2 {
+            args.push("-g".to_string());
+        } else if self.debug_level > 0 {
+            args.push("-C".to_string());
+            args.push(format!("debuginfo={}", self.debug_level));
+        }
+        if !self.debug_assertions {
+            args.push("--cfg".to_string());
+            args.push("ndebug".to_string());
+        }
+        args.push("-C".to_string());
+        args.push(self.debug_assertions_flag());
+        match self.overflow_checks {
+            Some(checks) => {
+                args.push("-C".to_string());
+                args.push(format!("overflow-checks={}", if checks {"on"} else {"off"}));
+            }
+            None => {}
+        }
+        match self.lto {
+            Off => {}
+            Thin => args.push_all(["-C".to_string(), "lto=thin".to_string()]),
+            Fat => args.push_all(["-C".to_string(), "lto".to_string()]),
+        }
+        match self.crt_static {
+            Some(crt_static) => {
+                args.push("-C".to_string());
+                args.push(format!("target-feature={}crt-static",
+                                  if crt_static {"+"} else {"-"}));
+            }
+            None => {}
+        }
+        if self.panic == Abort {
+            args.push("-C".to_string());
+            args.push("panic=abort".to_string());
+        }
+        if self.rpath {
+            args.push("-C".to_string());
+            args.push("rpath".to_string());
+        }
+        for cfg in self.cfgs.iter() {
+            args.push("--cfg".to_string());
+            args.push(cfg.clone());
+        }
+        args.extend(self.rustflags.iter().map(|f| f.clone()));
+        args
+    }
+
+    /// Checks for combinations of settings which rustc rejects or which don't
+    /// do what the user most likely intended.
+    pub fn validate(&self) -> CargoResult<()> {
+        let mut problems = Vec::new();
+        match self.codegen_units {
+            Some(n) if self.lto == Fat && n > 1 => {
+                problems.push(format!("lto cannot be combined with {} codegen \
+                                       units, set codegen-units to 1", n));
+            }
+            _ => {}
+        }
+        // libtest catches panics to report failing tests, which it can't do
+        // when they abort
+        if self.panic == Abort && self.test && self.harness {
+            problems.push("panic = \"abort\" cannot be used with the test \
+                           harness, set harness = false".to_string());
+        }
+
+        if problems.len() == 0 { return Ok(()) }
+        Err(human(format!("invalid profile `{}`:\n  {}", self.env,
+                          problems.connect("\n  "))))
+    }
+
+    /// LTO needs the whole crate graph at once, which defeats incremental
+    /// compilation, so the two can't be enabled together.
+    pub fn validate_incremental_lto(&self) -> CargoResult<()> {
+        if self.incremental && self.lto != Off {
+            return Err(human(format!("profile `{}` enables both lto and \
+                                      incremental compilation, which are \
+                                      incompatible", self.env)))
+        }
+        Ok(())
+    }
+
+    /// The profile for documenting a target built with this profile. Only the
+    /// `--cfg` settings carry over, as they decide which items get documented.
+    pub fn as_doc(&self) -> Profile {
+        Profile::default_doc().cfgs(self.cfgs.clone())
+                              .rustflags(self.get_cfg_rustflags())
+    }
+
+    /// The `--cfg` flags among this profile's extra rustc flags. Unlike the
+    /// rest, these are understood by rustdoc too.
+    pub fn get_cfg_rustflags(&self) -> Vec<String> {
+        let mut rustflags = Vec::new();
+        let mut flags = self.rustflags.iter();
+        loop {
+            let flag = match flags.next() { Some(flag) => flag, None => break };
+            if flag.as_slice() == "--cfg" {
+                rustflags.push(flag.clone());
+                rustflags.extend(flags.next().map(|f| f.clone()).into_iter());
+            } else if flag.as_slice().starts_with("--cfg=") {
+                rustflags.push(flag.clone());
+            }
+        }
+        rustflags
+    }
+
+    /// The profile used to compile a build dependency. Like plugins these are
+    /// built for the host, and they are never tested or documented.
+    pub fn for_build_dependency(&self) -> Profile {
+        self.clone().plugin(true).test(false).doctest(false).doc(false)
+                    .harness(false)
+    }
+
+    /// The profile used to build an example: the dev or release profile,
+    /// keeping any settings customized in this one. Examples are plain
+    /// binaries, so they never link the test harness.
+    pub fn for_example(&self, release: bool) -> Profile {
+        self.switch_env(if release {"release"} else {"compile"}).harness(false)
+    }
+
+    /// Adjusts this profile for running the output under the Miri
+    /// interpreter, which wants unoptimized code and can't do LTO.
+    pub fn for_miri(&self) -> Profile {
+        let mut cfgs = self.cfgs.clone();
+        if !cfgs.iter().any(|c| c.as_slice() == "miri") {
+            cfgs.push("miri".to_string());
+        }
+        self.clone().opt_level(Num(0)).lto(false).cfgs(cfgs)
+    }
+
+    /// The profile for linting with clippy. The build itself is unchanged,
+    /// `clippy_args` are passed along to clippy-driver.
+    pub fn for_clippy(&self, clippy_args: Vec<String>) -> Profile {
+        let mut ret = self.clone();
+        ret.clippy = true;
+        ret.rustflags.extend(clippy_args.into_iter());
+        ret
+    }
+
+    pub fn compiler_driver(&self) -> CompilerDriver {
+        if self.doc {
+            Rustdoc
+        } else if self.clippy {
+            Clippy
+        } else {
+            Rustc
+        }
+    }
+
+    pub fn get_opt_level(&self) -> OptLevel {
         self.opt_level
     }
 
@@ -214,19 +738,101 @@ impl Profile {
         self.codegen_units
     }
 
+    /// Whether any debuginfo is emitted.
     pub fn get_debug(&self) -> bool {
-        self.debug
+        self.debug_level > 0
+    }
+
+    pub fn get_debug_level(&self) -> u32 {
+        self.debug_level
+    }
+
+    /// Whether `debug_assert!` and friends are enabled. This is independent
+    /// of whether debuginfo is emitted.
+    pub fn get_debug_assertions(&self) -> bool {
+        self.debug_assertions
+    }
+
+    /// Whether arithmetic overflow is checked, if set explicitly. When `None`
+    /// rustc checks overflow whenever debug assertions are enabled.
+    pub fn get_overflow_checks(&self) -> Option<bool> {
+        self.overflow_checks
+    }
+
+    pub fn get_lto(&self) -> Lto {
+        self.lto
+    }
+
+    pub fn is_incremental(&self) -> bool {
+        self.incremental
+    }
+
+    pub fn get_cfgs(&self) -> &[String] {
+        self.cfgs.as_slice()
+    }
+
+    pub fn get_rustflags(&self) -> &[String] {
+        self.rustflags.as_slice()
+    }
+
+    /// Whether dynamic libraries this profile links against are found via an
+    /// rpath baked into the output. `args_for_rustc` passes this to rustc as
+    /// `-C rpath`.
+    pub fn get_rpath(&self) -> bool {
+        self.rpath
+    }
+
+    pub fn get_panic(&self) -> PanicStrategy {
+        self.panic
+    }
+
+    pub fn get_crt_static(&self) -> Option<bool> {
+        self.crt_static
+    }
+
+    pub fn get_test_threads(&self) -> Option<uint> {
+        self.test_threads
+    }
+
+    pub fn get_rustc_version(&self) -> Option<&str> {
+        self.rustc_version.as_ref().map(|s| s.as_slice())
+    }
+
+    /// Whether artifacts built with `other` must be rebuilt for this profile
+    /// because they came from a different version of rustc.
+    pub fn is_toolchain_changed(&self, other: &Profile) -> bool {
+        self.rustc_version != other.rustc_version
     }
 
     pub fn get_env(&self) -> &str {
         self.env.as_slice()
     }
 
+    /// The subdirectory of the output directory which artifacts built with
+    /// this profile belong in. Test and bench harnesses are kept apart from
+    /// each other and from the libraries they link against.
+    pub fn output_subdir(&self) -> &'static str {
+        if !self.test { return "deps" }
+        match self.env.as_slice() {
+            "bench" => "bench",
+            _ => "test",
+        }
+    }
+
     pub fn get_dest(&self) -> Option<&str> {
         self.dest.as_ref().map(|d| d.as_slice())
     }
 
-    pub fn opt_level(mut self, level: uint) -> Profile {
+    /// Describes the build this profile produces, e.g. `unoptimized +
+    /// debuginfo`, for use in status lines (callers wrap it in brackets).
+    pub fn descriptor(&self) -> String {
+        let optimized = self.opt_level.is_optimized();
+        let mut parts = vec![if optimized {"optimized"} else {"unoptimized"}];
+        if self.get_debug() { parts.push("debuginfo"); }
+        parts.connect(" + ")
+    }
+
+    pub fn opt_level(mut self, level: OptLevel) -> Profile {
         self.opt_level = level;
         self
     }
@@ -236,8 +842,38 @@ impl Profile {
         self
     }
 
-    pub fn debug(mut self, debug: bool) -> Profile {
-        self.debug = debug;
+    /// Shorthand for `debug_level`, where `true` means full debuginfo.
+    pub fn debug(self, debug: bool) -> Profile {
+        self.debug_level(if debug {2} else {0})
+    }
+
+    pub fn debug_level(mut self, level: u32) -> Profile {
+        self.debug_level = level;
+        self
+    }
+
+    pub fn debug_assertions(mut self, debug_assertions: bool) -> Profile {
+        self.debug_assertions = debug_assertions;
+        self
+    }
+
+    pub fn overflow_checks(mut self, overflow_checks: Option<bool>) -> Profile {
+        self.overflow_checks = overflow_checks;
+        self
+    }
+
+    /// Shorthand for `lto_mode`, where `true` means full (fat) LTO.
+    pub fn lto(self, lto: bool) -> Profile {
+        self.lto_mode(if lto {Fat} else {Off})
+    }
+
+    pub fn lto_mode(mut self, lto: Lto) -> Profile {
+        self.lto = lto;
+        self
+    }
+
+    pub fn incremental(mut self, incremental: bool) -> Profile {
+        self.incremental = incremental;
         self
     }
 
@@ -265,6 +901,54 @@ impl Profile {
         self.harness = harness;
         self
     }
+
+    pub fn cfgs(mut self, cfgs: Vec<String>) -> Profile {
+        self.cfgs = cfgs;
+        self
+    }
+
+    pub fn rustflags(mut self, flags: Vec<String>) -> Profile {
+        self.rustflags = flags;
+        self
+    }
+
+    /// This profile with the flags from `RUSTFLAGS` appended to its own, so
+    /// that they're part of the fingerprint.
+    pub fn with_env_rustflags(&self, env_flags: &[String]) -> Profile {
+        let mut ret = self.clone();
+        ret.rustflags.push_all(env_flags);
+        ret
+    }
+
+    pub fn emit(mut self, emit: Vec<String>) -> Profile {
+        self.emit = emit;
+        self
+    }
+
+    pub fn rpath(mut self, rpath: bool) -> Profile {
+        self.rpath = rpath;
+        self
+    }
+
+    pub fn panic(mut self, panic: PanicStrategy) -> Profile {
+        self.panic = panic;
+        self
+    }
+
+    pub fn crt_static(mut self, crt_static: Option<bool>) -> Profile {
+        self.crt_static = crt_static;
+        self
+    }
+
+    pub fn test_threads(mut self, threads: Option<uint>) -> Profile {
+        self.test_threads = threads;
+        self
+    }
+
+    pub fn rustc_version(mut self, version: Option<String>) -> Profile {
+        self.rustc_version = version;
+        self
+    }
 }
 
 impl<H: hash::Writer> hash::Hash<H> for Profile {
@@ -274,10 +958,23 @@ impl<H: hash::Writer> hash::Hash<H> for Profile {
         let Profile {
             opt_level,
             codegen_units,
-            debug,
+            debug_level,
+            debug_assertions,
+            overflow_checks,
+            lto,
+            incremental,
+            clippy,
             plugin,
             dest: ref dest,
             harness: harness,
+            emit_metadata_only,
+            emit: ref emit,
+            cfgs: ref cfgs,
+            rustflags: ref rustflags,
+            crt_static,
+            panic,
+            rpath,
+            rustc_version: ref rustc_version,
 
             // test flags are separated by file, not by profile hash, and
             // env/doc also don't matter for the actual contents of the output
@@ -286,8 +983,15 @@ impl<H: hash::Writer> hash::Hash<H> for Profile {
             env: _,
             test: _,
             doctest: _,
+
+            // only consulted when running the test binary
+            test_threads: _,
         } = *self;
-        (opt_level, codegen_units, debug, plugin, dest, harness).hash(into)
+        // split in two, as tuples only implement Hash up to 12 elements
+        ((opt_level, codegen_units, debug_level, debug_assertions,
+          overflow_checks, lto, incremental, clippy, plugin),
+         (dest, harness, emit_metadata_only, emit, cfgs, rustflags, crt_static,
+          panic, rpath, rustc_version)).hash(into)
     }
 }
 
@@ -298,6 +1002,9 @@ pub struct Target {
     src_path: Path,
     profile: Profile,
     metadata: Option<Metadata>,
+    crate_name: Option<String>,
+    required_features: Option<Vec<String>>,
+    out_dir: Option<Path>, // overrides the manifest's target_dir
 }
 
 #[deriving(Encodable)]
@@ -306,7 +1013,8 @@ pub struct SerializedTarget {
     name: String,
     src_path: String,
     profile: Profile,
-    metadata: Option<Metadata>
+    metadata: Option<Metadata>,
+    required_features: Option<Vec<String>>,
 }
 
 impl<E, S: Encoder<E>> Encodable<S, E> for Target {
@@ -321,7 +1029,8 @@ impl<E, S: Encoder<E>> Encodable<S, E> for Target {
             name: self.name.clone(),
             src_path: self.src_path.display().to_string(),
             profile: self.profile.clone(),
-            metadata: self.metadata.clone()
+            metadata: self.metadata.clone(),
+            required_features: self.required_features.clone(),
         }.encode(s)
     }
 }
@@ -337,10 +1046,12 @@ impl Show for Target {
 impl Manifest {
     pub fn new(summary: Summary, targets: Vec<Target>,
                target_dir: Path, doc_dir: Path, sources: Vec<SourceId>,
-               build: Vec<String>, exclude: Vec<String>) -> Manifest {
+               build: Vec<String>, exclude: Vec<String>,
+               profiles: HashMap<String, Profile>) -> Manifest {
         Manifest {
             summary: summary,
             authors: Vec::new(),
+            description: None,
             targets: targets,
             target_dir: target_dir,
             doc_dir: doc_dir,
@@ -348,9 +1059,20 @@ impl Manifest {
             build: build,
             warnings: Vec::new(),
             exclude: exclude,
+            include: Vec::new(),
+            no_std: false,
+            default_run_args: HashMap::new(),
+            package_overrides: HashMap::new(),
+            profiles: profiles,
         }
     }
 
+    /// The user-defined profile named `name`, if there is one. Callers fall
+    /// back to the built-in profiles otherwise.
+    pub fn get_profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.find_equiv(&name)
+    }
+
     pub fn get_summary(&self) -> &Summary {
         &self.summary
     }
@@ -367,18 +1089,277 @@ impl Manifest {
         self.get_summary().get_package_id().get_version()
     }
 
+    pub fn version_stability(&self) -> VersionStability {
+        let version = self.get_version();
+        if version.pre.len() > 0 {
+            PreRelease
+        } else if version.major == 0 {
+            Unstable
+        } else {
+            Stable
+        }
+    }
+
+    /// A canonical `name version (source)` string identifying this package,
+    /// in the same format as package ids in the lockfile.
+    pub fn package_ident(&self) -> String {
+        let source_id = self.get_package_id().get_source_id();
+        let source = if source_id.is_path() {
+            format!("path+{}", source_id.get_url())
+        } else {
+            source_id.to_url()
+        };
+        format!("{} {} ({})", self.get_name(), self.get_version(), source)
+    }
+
     pub fn get_authors(&self) -> &[String] {
         self.authors.as_slice()
     }
 
+    pub fn set_authors(&mut self, authors: Vec<String>) {
+        self.authors = authors;
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_ref().map(|s| s.as_slice())
+    }
+
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+    }
+
     pub fn get_dependencies(&self) -> &[Dependency] {
         self.get_summary().get_dependencies()
     }
 
+    /// The direct dependency named `name`, compared case-sensitively.
+    pub fn find_dependency(&self, name: &str) -> Option<&Dependency> {
+        self.get_dependencies().iter().find(|dep| dep.get_name() == name)
+    }
+
+    pub fn has_dependency(&self, name: &str) -> bool {
+        self.find_dependency(name).is_some()
+    }
+
     pub fn get_targets(&self) -> &[Target] {
         self.targets.as_slice()
     }
 
+    /// A copy of this manifest with only the targets named `name`, one for
+    /// each profile the target is built with.
+    pub fn only_target(&self, name: &str) -> CargoResult<Manifest> {
+        let targets: Vec<Target> = self.targets.iter().filter(|t| {
+            t.get_name() == name
+        }).map(|t| t.clone()).collect();
+
+        if targets.len() == 0 {
+            return Err(human(format!("no target named `{}` in package `{}`",
+                                     name, self.get_name())))
+        }
+        Ok(Manifest { targets: targets, .. self.clone() })
+    }
+
+    /// The targets built by a plain `cargo build` for `triple`: the lib and
+    /// all bins, but not examples, tests or benches.
+    pub fn default_build_targets(&self, triple: &str) -> Vec<&Target> {
+        self.targets.iter().filter(|t| {
+            let profile = t.get_profile();
+            profile.is_compile() && !profile.is_test() && !profile.is_doc() &&
+                (t.is_lib() || t.is_bin()) && self.target_buildable(*t, triple)
+        }).collect()
+    }
+
+    /// Whether `target` can be built for `triple` with the default features.
+    /// Targets are currently only excluded by their required features, the
+    /// triple is accepted for platform-specific gates.
+    pub fn target_buildable(&self, target: &Target, _triple: &str) -> bool {
+        let required = match target.get_required_features() {
+            Some(required) => required,
+            None => return true,
+        };
+
+        let features = self.summary.get_features();
+        let mut enabled = Vec::new();
+        let mut pending = self.default_features();
+        loop {
+            let feature = match pending.pop() { Some(f) => f, None => break };
+            if enabled.contains(&feature) { continue }
+            match features.find(&feature) {
+                Some(implied) => pending.push_all(implied.as_slice()),
+                None => {}
+            }
+            enabled.push(feature);
+        }
+        required.iter().all(|f| enabled.contains(f))
+    }
+
+    /// The features which are enabled unless `--no-default-features` is
+    /// passed, as listed by the `default` feature.
+    pub fn default_features(&self) -> Vec<String> {
+        match self.summary.get_features().find_equiv(&"default") {
+            Some(features) => features.clone(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The distinct profile environments (`compile`, `test`, ...) used by
+    /// this manifest's targets, in the order they first appear.
+    pub fn referenced_envs(&self) -> Vec<String> {
+        let mut envs: Vec<String> = Vec::new();
+        for target in self.targets.iter() {
+            let env = target.get_profile().get_env();
+            if !envs.iter().any(|e| e.as_slice() == env) {
+                envs.push(env.to_string());
+            }
+        }
+        envs
+    }
+
+    /// The locations of this package's path dependencies, e.g. for detecting
+    /// cycles between them before resolution.
+    pub fn path_dependency_roots(&self) -> Vec<Path> {
+        self.get_dependencies().iter().filter_map(|dep| {
+            let source_id = dep.get_source_id();
+            if !source_id.is_path() { return None }
+            source_id.get_url().to_file_path().ok()
+        }).collect()
+    }
+
+    /// Edges from this package to each of its direct dependencies, as
+    /// `(package, dependency)` name pairs.
+    pub fn dependency_edges(&self) -> Vec<(String, String)> {
+        let name = self.get_name();
+        self.get_dependencies().iter().map(|dep| {
+            (name.to_string(), dep.get_name().to_string())
+        }).collect()
+    }
+
+    /// The `CARGO_PKG_*` environment variables describing this package, set
+    /// when running its build artifacts.
+    pub fn runtime_env(&self) -> Vec<(String, String)> {
+        let version = self.get_version();
+        vec![
+            ("CARGO_PKG_NAME".to_string(), self.get_name().to_string()),
+            ("CARGO_PKG_VERSION".to_string(), version.to_string()),
+            ("CARGO_PKG_VERSION_MAJOR".to_string(), version.major.to_string()),
+            ("CARGO_PKG_VERSION_MINOR".to_string(), version.minor.to_string()),
+            ("CARGO_PKG_VERSION_PATCH".to_string(), version.patch.to_string()),
+            ("CARGO_PKG_AUTHORS".to_string(), self.authors.connect(":")),
+        ]
+    }
+
+    /// Checks that this manifest has everything needed to publish it, listing
+    /// every problem found in the returned error. Missing metadata which
+    /// doesn't prevent publishing is recorded as warnings instead.
+    pub fn validate_for_publish(&mut self) -> CargoResult<()> {
+        let mut problems = Vec::new();
+        if !self.targets.iter().any(|t| t.is_lib() || t.is_bin()) {
+            problems.push("no lib or bin targets to publish".to_string());
+        }
+        // a placeholder version needs at least a description of what it is
+        let placeholder = {
+            let version = self.get_version();
+            version.pre.len() > 0 ||
+                (version.major == 0 && version.minor == 0 && version.patch == 0)
+        };
+        if placeholder && self.description.is_none() {
+            problems.push(format!("version `{}` cannot be published without a \
+                                   description", self.get_version()));
+        }
+        if problems.len() > 0 {
+            return Err(human(format!("cannot publish `{}`:\n  {}",
+                                     self.get_name(), problems.connect("\n  "))))
+        }
+
+        if self.authors.len() == 0 {
+            self.add_warning("no authors specified".to_string());
+        }
+        if self.description.is_none() {
+            self.add_warning("no description specified".to_string());
+        }
+        Ok(())
+    }
+
+    /// Checks that the crate name of every target is a valid Rust
+    /// identifier, as rustc requires.
+    pub fn validate_crate_names(&self) -> CargoResult<()> {
+        let invalid: Vec<String> = self.targets.iter().filter_map(|t| {
+            let name = t.crate_name();
+            let valid = name.as_slice().chars().enumerate().all(|(i, c)| {
+                c == '_' || if i == 0 {c.is_alphabetic()} else {c.is_alphanumeric()}
+            });
+            if valid && name.len() > 0 { return None }
+            Some(format!("target `{}` has invalid crate name `{}`",
+                         t.get_name(), name))
+        }).collect();
+
+        if invalid.len() == 0 { return Ok(()) }
+        Err(human(format!("invalid crate names in `{}`:\n  {}", self.get_name(),
+                          invalid.connect("\n  "))))
+    }
+
+    /// Every distinct directory build output is placed in: the target
+    /// directory and any per-target overrides.
+    pub fn output_dirs(&self) -> Vec<Path> {
+        let mut dirs = vec![self.target_dir.clone()];
+        for dir in self.targets.iter().filter_map(|t| t.get_out_dir()) {
+            if !dirs.contains(dir) {
+                dirs.push(dir.clone());
+            }
+        }
+        dirs
+    }
+
+    /// The targets which may need to be rebuilt because `changed` was
+    /// modified: those whose source file it is, or whose source directory is
+    /// the nearest one containing it. A directory nested in another, like
+    /// `src/bin` in `src`, belongs only to the targets within it. This is
+    /// only a cheap filter to run before consulting dep-info.
+    pub fn targets_depending_on(&self, changed: &Path) -> Vec<&Target> {
+        let mut nearest: Option<Path> = None;
+        for t in self.targets.iter() {
+            for dir in t.source_dirs().into_iter() {
+                if !dir.is_ancestor_of(changed) { continue }
+                let deeper = nearest.as_ref().map_or(true, |n| {
+                    dir.components().count() > n.components().count()
+                });
+                if deeper { nearest = Some(dir) }
+            }
+        }
+
+        self.targets.iter().filter(|t| {
+            let src = t.get_src_path();
+            src == changed || nearest.as_ref() == Some(&src.dir_path())
+        }).collect()
+    }
+
+    /// Describes the compilation of each target for `triple`.
+    pub fn build_plan(&self, triple: &str) -> Vec<BuildUnit> {
+        self.targets.iter().map(|t| {
+            BuildUnit {
+                name: t.get_name().to_string(),
+                crate_types: t.rustc_crate_types(),
+                fingerprint: short_hash(t.get_profile()),
+                outputs: t.output_filenames(triple),
+            }
+        }).collect()
+    }
+
+    /// A hash of everything in the manifest which affects a build, for
+    /// detecting whether it changed. Warnings are derived data and ignored.
+    pub fn content_hash(&self) -> u64 {
+        let deps: Vec<String> = self.get_dependencies().iter().map(|d| {
+            d.to_string()
+        }).collect();
+        let mut features: Vec<(String, Vec<String>)> = self.summary
+            .get_features().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        features.sort();
+
+        let hasher = SipHasher::new_with_keys(0, 0);
+        hasher.hash(&(self.get_package_id(), deps, features, &self.targets,
+                      &self.build, &self.exclude, &self.include))
+    }
+
     pub fn get_target_dir(&self) -> &Path {
         &self.target_dir
     }
@@ -395,17 +1376,101 @@ impl Manifest {
         self.build.as_slice()
     }
 
+    /// Whether this package has a build command which must run before it is
+    /// compiled.
+    pub fn requires_build_script(&self) -> bool {
+        self.build.len() > 0
+    }
+
+    /// The `OUT_DIR` handed to this package's build command when building
+    /// into `base`, or `None` if it has no build command. This mirrors the
+    /// `native` directory of the build layout.
+    pub fn build_script_out_dir(&self, base: &Path) -> Option<Path> {
+        if !self.requires_build_script() { return None }
+        let dir = format!("{}-{}", self.get_name(), short_hash(self.get_package_id()));
+        Some(base.join("native").join(dir))
+    }
+
     pub fn add_warning(&mut self, s: String) {
-        self.warnings.push(s)
+        self.add_warning_kind(s, Unused)
+    }
+
+    /// Records a warning about this manifest. Warnings already recorded are
+    /// ignored, as a manifest may be processed more than once.
+    pub fn add_warning_kind(&mut self, s: String, kind: WarningKind) {
+        if !self.warnings.iter().any(|w| w.message == s) {
+            self.warnings.push(ManifestWarning { message: s, kind: kind })
+        }
+    }
+
+    pub fn get_warnings(&self) -> Vec<&str> {
+        self.warnings.iter().map(|w| w.message.as_slice()).collect()
     }
 
-    pub fn get_warnings(&self) -> &[String] {
+    pub fn get_detailed_warnings(&self) -> &[ManifestWarning] {
         self.warnings.as_slice()
     }
 
     pub fn get_exclude(&self) -> &[String] {
         self.exclude.as_slice()
     }
+
+    pub fn get_include(&self) -> &[String] {
+        self.include.as_slice()
+    }
+
+    pub fn set_include(&mut self, include: Vec<String>) {
+        self.include = include;
+    }
+
+    /// Whether `path`, relative to the package root, matches one of the
+    /// `exclude` globs.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude.iter().any(|p| glob_matches(p.as_slice(), path))
+    }
+
+    /// Whether `path`, relative to the package root, is part of the package.
+    /// If there are `include` globs, only the files matching them are, and
+    /// `exclude` is ignored. Otherwise every file not excluded is.
+    pub fn should_package(&self, path: &Path) -> bool {
+        if self.include.len() > 0 {
+            self.include.iter().any(|p| glob_matches(p.as_slice(), path))
+        } else {
+            !self.is_excluded(path)
+        }
+    }
+
+    /// Whether the crate is known to be `#![no_std]`. This is only a hint set
+    /// by tooling, the attribute in the crate root is what rustc obeys.
+    pub fn is_no_std(&self) -> bool {
+        self.no_std
+    }
+
+    pub fn set_no_std(&mut self, no_std: bool) {
+        self.no_std = no_std;
+    }
+
+    /// Arguments `cargo run` passes to the binary `bin` when none are given on
+    /// the command line.
+    pub fn get_default_run_args(&self, bin: &str) -> Option<&[String]> {
+        self.default_run_args.find_equiv(&bin).map(|args| args.as_slice())
+    }
+
+    pub fn set_default_run_args(&mut self, bin: &str, args: Vec<String>) {
+        self.default_run_args.insert(bin.to_string(), args);
+    }
+
+    /// The profile to build the dependency `pkg` with, if it is overridden
+    /// either by name or through the `"*"` wildcard.
+    pub fn get_package_override(&self, pkg: &str) -> Option<&Profile> {
+        self.package_overrides.find_equiv(&pkg).or_else(|| {
+            self.package_overrides.find_equiv(&"*")
+        })
+    }
+
+    pub fn add_package_override(&mut self, pkg: &str, profile: Profile) {
+        self.package_overrides.insert(pkg.to_string(), profile);
+    }
 }
 
 impl Target {
@@ -416,6 +1481,13 @@ impl Target {
         }
     }
 
+    /// The name of the dep-info file rustc emits alongside this target. The
+    /// profile's environment is part of the name, so that building the
+    /// target for e.g. `test` and `bench` doesn't overwrite the other's.
+    pub fn dep_info_filename(&self) -> String {
+        format!("{}-{}.d", self.file_stem(), self.profile.get_env())
+    }
+
     pub fn lib_target(name: &str, crate_targets: Vec<LibKind>,
                       src_path: &Path, profile: &Profile,
                       metadata: Metadata) -> Target {
@@ -424,10 +1496,20 @@ impl Target {
             name: name.to_string(),
             src_path: src_path.clone(),
             profile: profile.clone(),
-            metadata: Some(metadata)
+            metadata: Some(metadata),
+            crate_name: None,
+            required_features: None,
+            out_dir: None,
         }
     }
 
+    /// The lib target synthesized for a package which has a `src/lib.rs` but
+    /// no explicit `[lib]` section.
+    pub fn default_lib(pkg_name: &str, src_path: &Path, profile: &Profile,
+                       metadata: Metadata) -> Target {
+        Target::lib_target(pkg_name, vec![Lib], src_path, profile, metadata)
+    }
+
     pub fn bin_target(name: &str, src_path: &Path, profile: &Profile,
                       metadata: Option<Metadata>) -> Target {
         Target {
@@ -436,6 +1518,9 @@ impl Target {
             src_path: src_path.clone(),
             profile: profile.clone(),
             metadata: metadata,
+            crate_name: None,
+            required_features: None,
+            out_dir: None,
         }
     }
 
@@ -446,6 +1531,9 @@ impl Target {
             src_path: src_path.clone(),
             profile: profile.clone(),
             metadata: None,
+            crate_name: None,
+            required_features: None,
+            out_dir: None,
         }
     }
 
@@ -457,6 +1545,9 @@ impl Target {
             src_path: src_path.clone(),
             profile: profile.clone(),
             metadata: Some(metadata),
+            crate_name: None,
+            required_features: None,
+            out_dir: None,
         }
     }
 
@@ -468,6 +1559,9 @@ impl Target {
             src_path: src_path.clone(),
             profile: profile.clone(),
             metadata: Some(metadata),
+            crate_name: None,
+            required_features: None,
+            out_dir: None,
         }
     }
 
@@ -475,10 +1569,78 @@ impl Target {
         self.name.as_slice()
     }
 
+    /// The name passed to rustc as `--crate-name`. An explicit override wins,
+    /// otherwise this is the target name with dashes normalized away.
+    pub fn crate_name(&self) -> String {
+        match self.crate_name {
+            Some(ref name) => name.clone(),
+            None => self.name.replace("-", "_"),
+        }
+    }
+
+    pub fn get_crate_name_override(&self) -> Option<&str> {
+        self.crate_name.as_ref().map(|s| s.as_slice())
+    }
+
+    pub fn crate_name_override(mut self, name: Option<String>) -> Target {
+        self.crate_name = name;
+        self
+    }
+
+    /// The features which must be enabled for this target to be built.
+    pub fn get_required_features(&self) -> Option<&[String]> {
+        self.required_features.as_ref().map(|f| f.as_slice())
+    }
+
+    pub fn required_features(mut self, features: Option<Vec<String>>) -> Target {
+        self.required_features = features;
+        self
+    }
+
+    /// The directory this target's output is placed in, if it overrides the
+    /// manifest's target directory.
+    pub fn get_out_dir(&self) -> Option<&Path> {
+        self.out_dir.as_ref()
+    }
+
+    pub fn out_dir(mut self, out_dir: Option<Path>) -> Target {
+        self.out_dir = out_dir;
+        self
+    }
+
+    /// The directory which, by convention, holds the sources of this kind of
+    /// target, e.g. `examples` for an example.
+    pub fn conventional_dir(&self) -> &'static str {
+        if self.is_lib() { return "src" }
+
+        // binaries, examples, tests and benches only differ in where their
+        // sources live
+        let dir = self.src_path.dir_path();
+        match dir.filename_str() {
+            Some("examples") => "examples",
+            Some("tests") => "tests",
+            Some("benches") => "benches",
+            Some("bin") if dir.dir_path().filename_str() == Some("src") => "src/bin",
+            _ => "src",
+        }
+    }
+
     pub fn get_src_path(&self) -> &Path {
         &self.src_path
     }
 
+    // The directories whose sources may belong to this target: the one its
+    // source file is in and, for a target in `src`, the conventional
+    // `src/bin` too, which holds binaries even before there are any.
+    fn source_dirs(&self) -> Vec<Path> {
+        let dir = self.src_path.dir_path();
+        let mut dirs = vec![dir.clone()];
+        if self.conventional_dir() == "src" && dir.filename_str() == Some("src") {
+            dirs.push(dir.join("bin"));
+        }
+        dirs
+    }
+
     pub fn is_lib(&self) -> bool {
         match self.kind {
             LibTarget(_) => true,
@@ -493,6 +1655,26 @@ impl Target {
         }
     }
 
+    pub fn is_cdylib(&self) -> bool {
+        match self.kind {
+            LibTarget(ref kinds) => kinds.iter().any(|&k| k == Cdylib),
+            _ => false
+        }
+    }
+
+    pub fn is_proc_macro(&self) -> bool {
+        match self.kind {
+            LibTarget(ref kinds) => kinds.iter().any(|&k| k == ProcMacro),
+            _ => false
+        }
+    }
+
+    /// Whether this target runs inside the compiler, and hence must always be
+    /// built for the host rather than the target platform.
+    pub fn for_host(&self) -> bool {
+        self.profile.is_plugin() || self.is_proc_macro()
+    }
+
     pub fn is_rlib(&self) -> bool {
         match self.kind {
             LibTarget(ref kinds) =>
@@ -508,6 +1690,16 @@ impl Target {
         }
     }
 
+    /// Whether dependents of this package can link against this target.
+    pub fn is_linkable_dependency(&self) -> bool {
+        match self.kind {
+            LibTarget(ref kinds) => kinds.iter().any(|&k| {
+                k == Lib || k == Rlib || k == Dylib
+            }),
+            _ => false
+        }
+    }
+
     pub fn is_bin(&self) -> bool {
         match self.kind {
             BinTarget => true,
@@ -519,10 +1711,70 @@ impl Target {
         &self.profile
     }
 
+    /// Whether this target is compiled with `--test`, linking in libtest.
+    pub fn links_test_harness(&self) -> bool {
+        self.profile.is_test() && self.profile.uses_test_harness()
+    }
+
     pub fn get_metadata(&self) -> Option<&Metadata> {
         self.metadata.as_ref()
     }
 
+    /// The names of the files this target produces when compiled for
+    /// `triple`. Unlike the build context this doesn't ask rustc, so the
+    /// platform naming conventions are guessed from the triple.
+    pub fn output_filenames(&self, triple: &str) -> Vec<String> {
+        let stem = self.file_stem();
+        let windows = triple.contains("windows");
+        if self.is_bin() || self.profile.is_test() {
+            let exe = if windows {".exe"} else {""};
+            return vec![format!("{}{}", stem, exe)]
+        }
+
+        let mut ret = Vec::new();
+        if self.is_dylib() || self.is_cdylib() || self.is_proc_macro() {
+            let (prefix, suffix) = if windows {
+                ("", ".dll")
+            } else if triple.contains("apple") {
+                ("lib", ".dylib")
+            } else {
+                ("lib", ".so")
+            };
+            ret.push(format!("{}{}{}", prefix, stem, suffix));
+        }
+        if self.is_rlib() {
+            ret.push(format!("lib{}.rlib", stem));
+        }
+        if self.is_staticlib() {
+            ret.push(format!("lib{}.a", stem));
+        }
+        ret
+    }
+
+    /// Whether this target and `other` would write a file of the same name
+    /// when both are compiled for `triple`.
+    pub fn output_conflicts_with(&self, other: &Target, triple: &str) -> bool {
+        let theirs = other.output_filenames(triple);
+        self.output_filenames(triple).iter().any(|f| theirs.contains(f))
+    }
+
+    /// The `-C metadata` and `-C extra-filename` arguments for rustc. The
+    /// profile is mixed into the metadata so that builds of one target under
+    /// different profiles get distinct symbols. `profile` is the one the
+    /// target is really built with, as the root package's settings override
+    /// those of its dependencies.
+    pub fn codegen_metadata_args(&self, profile: &Profile) -> Vec<String> {
+        match self.metadata {
+            Some(ref m) => vec![
+                "-C".to_string(),
+                format!("metadata={}-{}", m.metadata, short_hash(profile)),
+                "-C".to_string(),
+                format!("extra-filename={}", m.extra_filename),
+            ],
+            None => Vec::new(),
+        }
+    }
+
     pub fn rustc_crate_types(&self) -> Vec<&'static str> {
         match self.kind {
             LibTarget(ref kinds) => {
@@ -532,3 +1784,1176 @@ impl Target {
         }
     }
 }
+
+// Matches `path` against a gitignore-style glob. Patterns without a `/`
+// match any file or directory of that name, at any depth, while others are
+// anchored at the package root. Matching a directory matches everything in
+// it, and a trailing `/` matches only directories.
+fn glob_matches(pattern: &str, path: &Path) -> bool {
+    let dir_only = pattern.ends_with("/");
+    let anchored = pattern.trim_right_chars('/').contains_char('/');
+    let pattern = Pattern::new(pattern.trim_left_chars('/').trim_right_chars('/'));
+    let components: Vec<&str> = path.str_components().filter_map(|c| c).collect();
+    if components.len() == 0 { return false }
+    let end = if dir_only {components.len() - 1} else {components.len()};
+
+    range(0, end).any(|i| {
+        if anchored {
+            pattern.matches(components.slice_to(i + 1).connect("/").as_slice())
+        } else {
+            pattern.matches(components[i])
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::default::Default;
+    use serialize::json;
+    use serialize::Decodable;
+
+    use core::source::{SourceId, RegistryKind};
+    use core::{Dependency, PackageId, Summary};
+    use util::{short_hash, CargoError, ToUrl};
+    use core::package_id::Metadata;
+    use super::{Manifest, Profile, Target, Rustc, Rustdoc, Clippy, Rlib, StaticLib};
+    use super::{LibKind, Lib, Dylib, Cdylib, ProcMacro, Off, Thin, Fat};
+    use super::{Unwind, Abort, OptLevel, Num, Size, SizeMin, ProfileOverrides};
+    use super::{ManifestWarning, Deprecation, Unused};
+    use super::{PreRelease, Unstable, Stable};
+
+    fn manifest(targets: Vec<Target>) -> Manifest {
+        manifest_with_deps(targets, Vec::new())
+    }
+
+    fn manifest_with_deps(targets: Vec<Target>,
+                          deps: Vec<Dependency>) -> Manifest {
+        manifest_with_features(targets, deps, HashMap::new())
+    }
+
+    fn manifest_with_features(targets: Vec<Target>, deps: Vec<Dependency>,
+                              features: HashMap<String, Vec<String>>) -> Manifest {
+        let pkg_id = PackageId::new("foo", "1.0.0", &registry()).unwrap();
+        let summary = Summary::new(pkg_id, deps, features).unwrap();
+        Manifest::new(summary, targets, Path::new("target"), Path::new("doc"),
+                      Vec::new(), Vec::new(), Vec::new(), HashMap::new())
+    }
+
+    fn registry() -> SourceId {
+        let url = "http://example.com".to_url().unwrap();
+        SourceId::new(RegistryKind, url)
+    }
+
+    fn metadata() -> Metadata {
+        Metadata {
+            metadata: "abc".to_string(),
+            extra_filename: "-abc".to_string(),
+        }
+    }
+
+    #[test]
+    fn profile_descriptor() {
+        assert_eq!(Profile::default_dev().descriptor().as_slice(),
+                   "unoptimized + debuginfo");
+        assert_eq!(Profile::default_release().descriptor().as_slice(),
+                   "optimized");
+        assert_eq!(Profile::default_release().debug(true).descriptor().as_slice(),
+                   "optimized + debuginfo");
+    }
+
+    #[test]
+    fn crate_name_override() {
+        let path = Path::new("src/main.rs");
+        let target = Target::bin_target("foo-bar", &path, &Profile::default_dev(),
+                                        None);
+        assert_eq!(target.crate_name().as_slice(), "foo_bar");
+        assert_eq!(target.get_crate_name_override(), None);
+
+        let target = target.crate_name_override(Some("renamed".to_string()));
+        assert_eq!(target.crate_name().as_slice(), "renamed");
+        assert_eq!(target.get_crate_name_override(), Some("renamed"));
+    }
+
+    #[test]
+    fn test_threads_not_hashed() {
+        let profile = Profile::default_test();
+        let threaded = Profile::default_test().test_threads(Some(4));
+        assert_eq!(threaded.get_test_threads(), Some(4));
+        assert_eq!(profile.get_test_threads(), None);
+        assert_eq!(short_hash(&profile), short_hash(&threaded));
+    }
+
+    #[test]
+    fn no_std_round_trip() {
+        let mut m = manifest(Vec::new());
+        assert!(!m.is_no_std());
+        assert!(json::encode(&m).as_slice().contains("\"no_std\":false"));
+
+        m.set_no_std(true);
+        assert!(m.is_no_std());
+        assert!(json::encode(&m).as_slice().contains("\"no_std\":true"));
+    }
+
+    #[test]
+    fn compiler_driver() {
+        assert_eq!(Profile::default_doc().compiler_driver(), Rustdoc);
+        assert_eq!(Profile::default_dev().compiler_driver(), Rustc);
+    }
+
+    #[test]
+    fn default_lib() {
+        let target = Target::default_lib("foo", &Path::new("src/lib.rs"),
+                                         &Profile::default_dev(), metadata());
+        assert!(target.is_lib());
+        assert_eq!(target.get_name(), "foo");
+        assert_eq!(target.rustc_crate_types(), vec!["lib"]);
+    }
+
+    #[test]
+    fn validate_profile() {
+        assert!(Profile::default_release().validate().is_ok());
+        assert!(Profile::default_release().lto(true).codegen_units(Some(1))
+                                          .validate().is_ok());
+
+        let err = Profile::default_release().lto(true).codegen_units(Some(4))
+                                            .validate().unwrap_err();
+        assert!(err.description().as_slice().contains("codegen units"));
+    }
+
+    #[test]
+    fn validate_panic_abort() {
+        assert!(Profile::default_release().panic(Abort).validate().is_ok());
+        assert!(Profile::default_test().harness(false).panic(Abort).validate().is_ok());
+
+        for profile in [Profile::default_test(), Profile::default_bench()].iter() {
+            let err = profile.clone().panic(Abort).validate().unwrap_err();
+            assert!(err.description().as_slice().contains("test harness"));
+        }
+    }
+
+    #[test]
+    fn default_build_targets() {
+        let dev = Profile::default_dev();
+        let m = manifest(vec![
+            Target::default_lib("foo", &Path::new("src/lib.rs"), &dev,
+                                metadata()),
+            Target::bin_target("a", &Path::new("src/bin/a.rs"), &dev, None),
+            Target::bin_target("b", &Path::new("src/bin/b.rs"), &dev, None),
+            Target::example_target("ex", &Path::new("examples/ex.rs"),
+                                   &Profile::default_test().test(false)),
+            Target::test_target("t", &Path::new("tests/t.rs"),
+                                &Profile::default_test(), metadata()),
+        ]);
+        let names: Vec<&str> = m.default_build_targets("x86_64-unknown-linux-gnu")
+                                .iter().map(|t| {
+            t.get_name()
+        }).collect();
+        assert_eq!(names, vec!["foo", "a", "b"]);
+    }
+
+    #[test]
+    fn build_dependency_profile() {
+        let profile = Profile::default_test().opt_level(Num(2)).for_build_dependency();
+        assert!(profile.is_plugin());
+        assert!(!profile.is_test());
+        assert!(!profile.is_doc());
+        assert!(!profile.uses_test_harness());
+        assert_eq!(profile.get_opt_level(), Num(2));
+        assert!(profile.get_debug());
+        assert!(profile.validate().is_ok());
+    }
+
+    #[test]
+    fn default_run_args() {
+        let mut m = manifest(Vec::new());
+        assert!(m.get_default_run_args("foo").is_none());
+
+        m.set_default_run_args("foo", vec!["--port".to_string(), "80".to_string()]);
+        assert_eq!(m.get_default_run_args("foo"),
+                   Some(["--port".to_string(), "80".to_string()].as_slice()));
+        assert!(m.get_default_run_args("bar").is_none());
+        assert!(json::encode(&m).as_slice()
+                    .contains("\"default_run_args\":{\"foo\":[\"--port\",\"80\"]}"));
+    }
+
+    #[test]
+    fn package_overrides() {
+        let mut m = manifest(Vec::new());
+        assert!(m.get_package_override("bar").is_none());
+
+        m.add_package_override("*", Profile::default_dev().opt_level(Num(2)));
+        m.add_package_override("bar", Profile::default_dev().opt_level(Num(3)));
+        assert_eq!(m.get_package_override("bar").unwrap().get_opt_level(), Num(3));
+        assert_eq!(m.get_package_override("baz").unwrap().get_opt_level(), Num(2));
+    }
+
+    #[test]
+    fn links_test_harness() {
+        let path = Path::new("tests/foo.rs");
+        let harnessed = Target::test_target("foo", &path, &Profile::default_test(),
+                                            metadata());
+        let unharnessed = Target::test_target("foo", &path,
+                                              &Profile::default_test().harness(false),
+                                              metadata());
+        let bin = Target::bin_target("foo", &Path::new("src/main.rs"),
+                                     &Profile::default_dev(), None);
+        assert!(harnessed.links_test_harness());
+        assert!(!unharnessed.links_test_harness());
+        assert!(!bin.links_test_harness());
+    }
+
+    #[test]
+    fn miri_profile() {
+        let base = Profile::default_release().lto(true);
+        let miri = base.for_miri();
+        assert_eq!(miri.get_lto(), Off);
+        assert_eq!(miri.get_opt_level(), Num(0));
+        assert_eq!(miri.get_cfgs(), ["miri".to_string()].as_slice());
+        assert_eq!(miri.for_miri().get_cfgs().len(), 1);
+    }
+
+    #[test]
+    fn referenced_envs() {
+        let m = manifest(vec![
+            Target::bin_target("a", &Path::new("src/bin/a.rs"),
+                               &Profile::default_dev(), None),
+            Target::test_target("a", &Path::new("src/bin/a.rs"),
+                                &Profile::default_test(), metadata()),
+            Target::bin_target("b", &Path::new("src/bin/b.rs"),
+                               &Profile::default_dev(), None),
+        ]);
+        assert_eq!(m.referenced_envs(),
+                   vec!["compile".to_string(), "test".to_string()]);
+    }
+
+    #[test]
+    fn dep_info_filename() {
+        let lib = Target::default_lib("foo", &Path::new("src/lib.rs"),
+                                      &Profile::default_dev(), metadata());
+        assert_eq!(lib.dep_info_filename().as_slice(), "foo-abc-compile.d");
+
+        let bin = Target::bin_target("foo", &Path::new("src/main.rs"),
+                                     &Profile::default_dev(), None);
+        assert_eq!(bin.dep_info_filename().as_slice(), "foo-compile.d");
+
+        let names: Vec<String> = [Profile::default_test(), Profile::default_bench()]
+                                     .iter().map(|p| {
+            Target::default_lib("foo", &Path::new("src/lib.rs"), p, metadata())
+                .dep_info_filename()
+        }).collect();
+        assert_eq!(names, vec!["foo-abc-test.d".to_string(),
+                               "foo-abc-bench.d".to_string()]);
+    }
+
+    #[test]
+    fn serialized_target_includes_profile() {
+        let profile = Profile::default_release().lto(true).codegen_units(Some(1))
+                                                .cfgs(vec!["foo".to_string()]);
+        let target = Target::bin_target("foo", &Path::new("src/main.rs"),
+                                        &profile, None);
+        let json = json::encode(&target);
+        for field in ["\"opt_level\":\"3\"", "\"codegen_units\":1", "\"debug_level\":0",
+                      "\"lto\":\"Fat\"", "\"cfgs\":[\"foo\"]", "\"harness\":true",
+                      "\"dest\":\"release\""].iter() {
+            assert!(json.as_slice().contains(*field), "{} not in {}", field, json);
+        }
+    }
+
+    #[test]
+    fn content_hash() {
+        let bin = Target::bin_target("foo", &Path::new("src/main.rs"),
+                                     &Profile::default_dev(), None);
+        let m1 = manifest(vec![bin.clone()]);
+        let mut m2 = manifest(vec![bin.clone()]);
+        m2.add_warning("unused manifest key: foo".to_string());
+        assert_eq!(m1.content_hash(), m2.content_hash());
+
+        let m3 = manifest(vec![bin, Target::bin_target("bar",
+                                                       &Path::new("src/bin/bar.rs"),
+                                                       &Profile::default_dev(),
+                                                       None)]);
+        assert!(m1.content_hash() != m3.content_hash());
+    }
+
+    #[test]
+    fn switch_env_keeps_overrides() {
+        let dev = Profile::default_dev().codegen_units(Some(1));
+        let release = dev.switch_env("release");
+        assert_eq!(release.get_env(), "release");
+        assert_eq!(release.get_codegen_units(), Some(1));
+        assert_eq!(release.get_opt_level(), Num(3));
+        assert!(!release.get_debug());
+        assert_eq!(release.get_dest(), Some("release"));
+    }
+
+    #[test]
+    fn linkable_dependency() {
+        let path = Path::new("src/lib.rs");
+        let dev = Profile::default_dev();
+        let rlib = Target::lib_target("foo", vec![Rlib], &path, &dev, metadata());
+        let staticlib = Target::lib_target("foo", vec![StaticLib], &path, &dev,
+                                           metadata());
+        let bin = Target::bin_target("foo", &Path::new("src/main.rs"), &dev, None);
+        assert!(rlib.is_linkable_dependency());
+        assert!(!staticlib.is_linkable_dependency());
+        assert!(!bin.is_linkable_dependency());
+    }
+
+    #[test]
+    fn incremental_lto_conflict() {
+        let profile = Profile::default_release();
+        assert!(profile.clone().lto(true).validate_incremental_lto().is_ok());
+        assert!(profile.clone().incremental(true).validate_incremental_lto().is_ok());
+        assert!(profile.lto(true).incremental(true)
+                       .validate_incremental_lto().is_err());
+    }
+
+    #[test]
+    fn validate_for_publish() {
+        let bin = Target::bin_target("foo", &Path::new("src/main.rs"),
+                                     &Profile::default_dev(), None);
+        let mut m = manifest(vec![bin]);
+        m.validate_for_publish().unwrap();
+        assert_eq!(m.get_warnings(), vec!["no authors specified",
+                                          "no description specified"]);
+
+        let bin = Target::bin_target("foo", &Path::new("src/main.rs"),
+                                     &Profile::default_dev(), None);
+        let mut m = manifest(vec![bin]);
+        m.set_authors(vec!["foo@example.com".to_string()]);
+        m.set_description(Some("A foo".to_string()));
+        m.validate_for_publish().unwrap();
+        assert!(m.get_warnings().is_empty());
+
+        let err = manifest(Vec::new()).validate_for_publish().unwrap_err();
+        let desc = err.description();
+        assert!(desc.as_slice().contains("no lib or bin targets"));
+        assert!(!desc.as_slice().contains("no authors"));
+    }
+
+    #[test]
+    fn validate_for_publish_placeholder_version() {
+        let versioned = |version: &str| {
+            let pkg_id = PackageId::new("foo", version, &registry()).unwrap();
+            let summary = Summary::new(pkg_id, Vec::new(), HashMap::new()).unwrap();
+            let bin = Target::bin_target("foo", &Path::new("src/main.rs"),
+                                         &Profile::default_dev(), None);
+            Manifest::new(summary, vec![bin], Path::new("target"), Path::new("doc"),
+                          Vec::new(), Vec::new(), Vec::new(), HashMap::new())
+        };
+
+        for version in ["0.0.0", "1.0.0-beta"].iter() {
+            let mut m = versioned(*version);
+            let err = m.validate_for_publish().unwrap_err();
+            assert!(err.description().as_slice().contains("without a description"));
+
+            m.set_description(Some("A foo".to_string()));
+            assert!(m.validate_for_publish().is_ok());
+        }
+        assert!(versioned("0.1.0").validate_for_publish().is_ok());
+    }
+
+    #[test]
+    fn doc_profile_keeps_cfgs() {
+        let profile = Profile::default_dev()
+            .cfgs(vec!["feature=\"x\"".to_string()])
+            .rustflags(vec!["-C".to_string(), "target-cpu=native".to_string(),
+                            "--cfg".to_string(), "foo".to_string()]);
+        let doc = profile.as_doc();
+        assert!(doc.is_doc());
+        assert_eq!(doc.get_cfgs(), ["feature=\"x\"".to_string()].as_slice());
+        assert_eq!(doc.get_rustflags(),
+                   ["--cfg".to_string(), "foo".to_string()].as_slice());
+
+        let profile = profile.rustflags(vec!["--cfg=bar".to_string(), "-g".to_string()]);
+        assert_eq!(profile.get_cfg_rustflags(), vec!["--cfg=bar".to_string()]);
+    }
+
+    #[test]
+    fn output_conflicts() {
+        let triple = "x86_64-unknown-linux-gnu";
+        let dev = Profile::default_dev();
+        let a = Target::bin_target("foo", &Path::new("src/main.rs"), &dev, None);
+        let b = Target::bin_target("foo", &Path::new("src/bin/foo.rs"), &dev, None);
+        assert!(a.output_conflicts_with(&b, triple));
+
+        let lib = Target::default_lib("foo", &Path::new("src/lib.rs"), &dev,
+                                      metadata());
+        let bin = Target::bin_target("bar", &Path::new("src/main.rs"), &dev, None);
+        assert_eq!(lib.output_filenames(triple), vec!["libfoo-abc.rlib".to_string()]);
+        assert!(!lib.output_conflicts_with(&bin, triple));
+    }
+
+    #[test]
+    fn runtime_env() {
+        let env = manifest(Vec::new()).runtime_env();
+        let find = |key: &str| {
+            env.iter().find(|&&(ref k, _)| k.as_slice() == key)
+               .map(|&(_, ref v)| v.as_slice())
+        };
+        assert_eq!(find("CARGO_PKG_NAME"), Some("foo"));
+        assert_eq!(find("CARGO_PKG_VERSION"), Some("1.0.0"));
+        assert_eq!(find("CARGO_PKG_VERSION_MAJOR"), Some("1"));
+    }
+
+    #[test]
+    fn conventional_dir() {
+        let dev = Profile::default_dev();
+        let lib = Target::default_lib("foo", &Path::new("src/lib.rs"), &dev,
+                                      metadata());
+        let example = Target::example_target("ex", &Path::new("examples/ex.rs"),
+                                             &dev);
+        let test = Target::test_target("t", &Path::new("tests/t.rs"),
+                                       &Profile::default_test(), metadata());
+        assert_eq!(lib.conventional_dir(), "src");
+        assert_eq!(example.conventional_dir(), "examples");
+        assert_eq!(test.conventional_dir(), "tests");
+
+        let main = Target::bin_target("foo", &Path::new("/foo/src/main.rs"), &dev, None);
+        let bin = Target::bin_target("bar", &Path::new("/foo/src/bin/bar.rs"), &dev, None);
+        assert_eq!(main.conventional_dir(), "src");
+        assert_eq!(bin.conventional_dir(), "src/bin");
+    }
+
+    #[test]
+    fn codegen_metadata_args() {
+        let dev = Profile::default_dev();
+        let lib = Target::default_lib("foo", &Path::new("src/lib.rs"), &dev,
+                                      metadata());
+        let args = lib.codegen_metadata_args(&dev);
+        assert_eq!(args.len(), 4);
+        assert_eq!(args[0].as_slice(), "-C");
+        assert!(args[1].as_slice().starts_with("metadata=abc-"));
+        assert_eq!(args[3].as_slice(), "extra-filename=-abc");
+
+        let release = Target::default_lib("foo", &Path::new("src/lib.rs"),
+                                          &Profile::default_release(), metadata());
+        assert!(release.codegen_metadata_args(release.get_profile())[1] != args[1]);
+
+        // the effective profile is hashed, not the one the target declares
+        let overridden = dev.clone().opt_level(Num(3));
+        assert!(lib.codegen_metadata_args(&overridden)[1] != args[1]);
+
+        let bin = Target::bin_target("foo", &Path::new("src/main.rs"), &dev, None);
+        assert!(bin.codegen_metadata_args(&dev).is_empty());
+    }
+
+    #[test]
+    fn targets_depending_on() {
+        let m = manifest(vec![
+            Target::default_lib("foo", &Path::new("/foo/src/lib.rs"),
+                                &Profile::default_dev(), metadata()),
+            Target::test_target("t", &Path::new("/foo/tests/t.rs"),
+                                &Profile::default_test(), metadata()),
+        ]);
+        let names = |path: &str| -> Vec<String> {
+            m.targets_depending_on(&Path::new(path)).iter().map(|t| {
+                t.get_name().to_string()
+            }).collect()
+        };
+        assert_eq!(names("/foo/src/util/mod.rs"), vec!["foo".to_string()]);
+        assert_eq!(names("/foo/tests/t.rs"), vec!["t".to_string()]);
+        assert!(names("/foo/README.md").is_empty());
+    }
+
+    #[test]
+    fn targets_depending_on_bin_dir() {
+        let dev = Profile::default_dev();
+        let lib = Target::default_lib("foo", &Path::new("/foo/src/lib.rs"), &dev,
+                                      metadata());
+        let bin = Target::bin_target("bar", &Path::new("/foo/src/bin/bar.rs"), &dev,
+                                     None);
+        let m = manifest(vec![lib.clone(), bin]);
+        let names = m.targets_depending_on(&Path::new("/foo/src/bin/bar/util.rs"));
+        let names: Vec<&str> = names.iter().map(|t| t.get_name()).collect();
+        assert_eq!(names, vec!["bar"]);
+
+        // src/bin holds binaries by convention, even when there are none yet
+        let m = manifest(vec![lib]);
+        assert!(m.targets_depending_on(&Path::new("/foo/src/bin/new.rs")).is_empty());
+        assert_eq!(m.targets_depending_on(&Path::new("/foo/src/lib.rs")).len(), 1);
+    }
+
+    #[test]
+    fn doctest_runnable() {
+        let host = "x86_64-unknown-linux-gnu";
+        let profile = Profile::default_doc().doctest(true);
+        assert!(profile.doctest_runnable(&None, host));
+        assert!(profile.doctest_runnable(&Some(host.to_string()), host));
+        assert!(!profile.doctest_runnable(&Some("aarch64-unknown-linux-gnu".to_string()),
+                                          host));
+        assert!(!Profile::default_dev().doctest_runnable(&None, host));
+    }
+
+    #[test]
+    fn build_plan() {
+        let dev = Profile::default_dev();
+        let m = manifest(vec![
+            Target::default_lib("foo", &Path::new("src/lib.rs"), &dev, metadata()),
+            Target::bin_target("foo", &Path::new("src/main.rs"), &dev, None),
+        ]);
+        let plan = m.build_plan("x86_64-unknown-linux-gnu");
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].outputs, vec!["libfoo-abc.rlib".to_string()]);
+        assert_eq!(plan[1].crate_types, vec!["bin"]);
+
+        let json = json::encode(&plan);
+        assert!(json.as_slice().contains("\"crate_types\":[\"lib\"]"));
+        assert!(json.as_slice().contains("\"outputs\":[\"foo\"]"));
+        assert!(json.as_slice().contains(
+            format!("\"fingerprint\":\"{}\"", short_hash(&dev)).as_slice()));
+    }
+
+    #[test]
+    fn clippy_profile() {
+        let base = Profile::default_dev().opt_level(Num(1));
+        let clippy = base.for_clippy(vec!["-W".to_string(),
+                                          "clippy::pedantic".to_string()]);
+        assert_eq!(clippy.compiler_driver(), Clippy);
+        assert_eq!(clippy.compiler_driver().program(), "clippy-driver");
+        assert_eq!(clippy.get_rustflags(),
+                   ["-W".to_string(), "clippy::pedantic".to_string()].as_slice());
+        assert_eq!(clippy.get_opt_level(), Num(1));
+        assert!(short_hash(&clippy) != short_hash(&base));
+    }
+
+    #[test]
+    fn path_dependency_roots() {
+        let path = SourceId::for_path(&Path::new("/foo/bar")).unwrap();
+        let m = manifest_with_deps(Vec::new(), vec![
+            Dependency::parse("bar", None, &path).unwrap(),
+            Dependency::parse("baz", Some("1.0.0"), &registry()).unwrap(),
+        ]);
+        assert_eq!(m.path_dependency_roots(), vec![Path::new("/foo/bar")]);
+    }
+
+    #[test]
+    fn crt_static_args() {
+        let base = Profile::default_release();
+        assert!(!base.args_for_rustc().iter().any(|a| a.as_slice().contains("crt-static")));
+
+        let on = base.clone().crt_static(Some(true));
+        let args = on.args_for_rustc();
+        assert!(args.contains(&"target-feature=+crt-static".to_string()));
+        assert_eq!(on.get_crt_static(), Some(true));
+        assert!(short_hash(&on) != short_hash(&base));
+
+        let off = base.clone().crt_static(Some(false));
+        assert!(off.args_for_rustc().contains(&"target-feature=-crt-static".to_string()));
+        assert!(short_hash(&off) != short_hash(&on));
+    }
+
+    #[test]
+    fn dependency_edges() {
+        let m = manifest_with_deps(Vec::new(), vec![
+            Dependency::parse("bar", Some("1.0.0"), &registry()).unwrap(),
+            Dependency::parse("baz", Some("0.2.0"), &registry()).unwrap(),
+        ]);
+        assert_eq!(m.dependency_edges(), vec![
+            ("foo".to_string(), "bar".to_string()),
+            ("foo".to_string(), "baz".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn cdylib_target() {
+        assert_eq!(LibKind::from_str("cdylib").unwrap(), Cdylib);
+        assert_eq!(Cdylib.crate_type(), "cdylib");
+        assert!(LibKind::from_str("foo").is_err());
+
+        let dev = Profile::default_dev();
+        let path = Path::new("src/lib.rs");
+        let target = Target::lib_target("foo", vec![Cdylib], &path, &dev, metadata());
+        assert!(target.is_cdylib());
+        assert!(!target.is_dylib());
+        assert!(!target.is_linkable_dependency());
+        assert_eq!(target.rustc_crate_types(), vec!["cdylib"]);
+        assert_eq!(target.output_filenames("x86_64-unknown-linux-gnu"),
+                   vec!["libfoo-abc.so".to_string()]);
+        assert!(json::encode(&target).as_slice().contains("\"kind\":[\"cdylib\"]"));
+    }
+
+    #[test]
+    fn lto_modes() {
+        let base = Profile::default_release();
+        assert_eq!(base.get_lto(), Off);
+        assert_eq!(base.clone().lto(true).get_lto(), Fat);
+        assert_eq!(base.clone().lto(true).lto(false).get_lto(), Off);
+
+        let thin = base.clone().lto_mode(Thin);
+        let fat = base.clone().lto_mode(Fat);
+        assert!(short_hash(&thin) != short_hash(&fat));
+        assert!(short_hash(&thin) != short_hash(&base));
+
+        assert!(thin.args_for_rustc().contains(&"lto=thin".to_string()));
+        assert!(fat.args_for_rustc().contains(&"lto".to_string()));
+        assert!(!base.args_for_rustc().iter().any(|a| a.as_slice().starts_with("lto")));
+
+        // thin LTO is meant to be used with parallel codegen
+        assert!(thin.codegen_units(Some(4)).validate().is_ok());
+    }
+
+    #[test]
+    fn proc_macro_target() {
+        assert_eq!(LibKind::from_str("proc-macro").unwrap(), ProcMacro);
+        assert_eq!(ProcMacro.crate_type(), "proc-macro");
+        assert_eq!(LibKind::from_strs(vec!["proc-macro"]).unwrap(), vec![ProcMacro]);
+        assert!(LibKind::from_strs(vec!["proc-macro", "rlib"]).is_err());
+
+        let dev = Profile::default_dev();
+        let path = Path::new("src/lib.rs");
+        let target = Target::lib_target("foo", vec![ProcMacro], &path, &dev, metadata());
+        assert!(target.is_proc_macro());
+        assert!(target.for_host());
+        assert!(!target.is_linkable_dependency());
+        assert!(!Target::default_lib("foo", &path, &dev, metadata()).for_host());
+        assert!(json::encode(&target).as_slice().contains("\"kind\":[\"proc-macro\"]"));
+    }
+
+    #[test]
+    fn default_features() {
+        let mut features = HashMap::new();
+        features.insert("default".to_string(), vec!["a".to_string()]);
+        features.insert("a".to_string(), Vec::new());
+        features.insert("b".to_string(), Vec::new());
+        let m = manifest_with_features(Vec::new(), Vec::new(), features);
+        assert_eq!(m.default_features(), vec!["a".to_string()]);
+
+        let mut features = HashMap::new();
+        features.insert("default".to_string(), Vec::new());
+        let m = manifest_with_features(Vec::new(), Vec::new(), features);
+        assert!(m.default_features().is_empty());
+        assert!(manifest(Vec::new()).default_features().is_empty());
+    }
+
+    #[test]
+    fn lib_kind_from_str_is_lenient() {
+        assert_eq!(LibKind::from_str("RLib").unwrap(), Rlib);
+        assert_eq!(LibKind::from_str(" dylib").unwrap(), Dylib);
+        assert_eq!(LibKind::from_str("StaticLib \t").unwrap(), StaticLib);
+        assert_eq!(LibKind::from_strs(vec![" Lib", "RLIB "]).unwrap(),
+                   vec![Lib, Rlib]);
+
+        let err = LibKind::from_str(" Foo ").unwrap_err();
+        assert!(err.description().as_slice().starts_with("` Foo ` was not one of"));
+        assert!(LibKind::from_strs(vec!["rlib", "r lib"]).is_err());
+    }
+
+    #[test]
+    fn lib_kind_suggestions() {
+        let err = LibKind::from_str("dynlib").unwrap_err();
+        assert_eq!(err.description().as_slice(),
+                   "`dynlib` is not a valid crate type, did you mean `dylib`?");
+        let err = LibKind::from_str("statclib").unwrap_err();
+        assert!(err.description().as_slice().contains("did you mean `staticlib`?"));
+
+        assert_eq!(LibKind::from_str("DYLIB").unwrap(), Dylib);
+
+        let err = LibKind::from_str("executable").unwrap_err();
+        assert_eq!(err.description().as_slice(),
+                   "`executable` was not one of \
+                    lib|rlib|dylib|cdylib|staticlib|proc-macro");
+    }
+
+    #[test]
+    fn output_subdir() {
+        let test = Profile::default_test();
+        let bench = Profile::default_bench();
+        assert_eq!(test.output_subdir(), "test");
+        assert_eq!(bench.output_subdir(), "bench");
+        assert!(test.output_subdir() != bench.output_subdir());
+        assert_eq!(Profile::default_dev().output_subdir(), "deps");
+        assert_eq!(Profile::default_release().output_subdir(), "deps");
+    }
+
+    #[test]
+    fn required_features() {
+        let path = Path::new("examples/foo.rs");
+        let target = Target::example_target("foo", &path, &Profile::default_dev());
+        assert_eq!(target.get_required_features(), None);
+        assert!(json::encode(&target).as_slice().contains("\"required_features\":null"));
+
+        let target = target.required_features(Some(vec!["a".to_string()]));
+        assert_eq!(target.get_required_features(), Some(["a".to_string()].as_slice()));
+        assert!(json::encode(&target).as_slice()
+                                     .contains("\"required_features\":[\"a\"]"));
+    }
+
+    #[test]
+    fn lto_off_by_default_and_hashed() {
+        for profile in [Profile::default_release(), Profile::default_bench()].iter() {
+            assert_eq!(profile.get_lto(), Off);
+            let lto = profile.clone().lto(true);
+            assert!(short_hash(profile) != short_hash(&lto));
+        }
+    }
+
+    #[test]
+    fn validate_crate_names() {
+        let dev = Profile::default_dev();
+        let m = manifest(vec![
+            Target::default_lib("foo-bar", &Path::new("src/lib.rs"), &dev, metadata()),
+            Target::bin_target("_foo2", &Path::new("src/main.rs"), &dev, None),
+        ]);
+        assert!(m.validate_crate_names().is_ok());
+
+        let m = manifest(vec![
+            Target::bin_target("2foo", &Path::new("src/bin/2foo.rs"), &dev, None),
+        ]);
+        let err = m.validate_crate_names().unwrap_err();
+        assert!(err.description().as_slice()
+                   .contains("target `2foo` has invalid crate name `2foo`"));
+    }
+
+    #[test]
+    fn panic_strategy() {
+        let defaults = [Profile::default_dev(), Profile::default_test(),
+                        Profile::default_bench(), Profile::default_release(),
+                        Profile::default_doc()];
+        for profile in defaults.iter() {
+            assert_eq!(profile.get_panic(), Unwind);
+            assert!(!profile.args_for_rustc().contains(&"panic=abort".to_string()));
+        }
+
+        let base = Profile::default_release();
+        let abort = base.clone().panic(Abort);
+        assert_eq!(abort.get_panic(), Abort);
+        assert!(short_hash(&abort) != short_hash(&base));
+        assert!(abort.args_for_rustc().contains(&"panic=abort".to_string()));
+        assert_eq!(abort.switch_env("bench").get_panic(), Abort);
+    }
+
+    #[test]
+    fn check_profile() {
+        let check = Profile::default_check();
+        let dev = Profile::default_dev();
+        assert!(check.is_check());
+        assert!(!dev.is_check());
+        assert_eq!(check.get_opt_level(), Num(0));
+        assert!(check.args_for_rustc().contains(&"--emit=metadata".to_string()));
+        assert!(!dev.args_for_rustc().contains(&"--emit=metadata".to_string()));
+        assert!(short_hash(&check) != short_hash(&dev.clone().debug(false)));
+        assert!(dev.switch_env("check").is_check());
+    }
+
+    #[test]
+    fn rpath_off_by_default() {
+        let defaults = [Profile::default_dev(), Profile::default_test(),
+                        Profile::default_bench(), Profile::default_release(),
+                        Profile::default_doc(), Profile::default_check()];
+        for profile in defaults.iter() {
+            assert!(!profile.get_rpath());
+        }
+
+        let base = Profile::default_dev();
+        let rpath = base.clone().rpath(true);
+        assert!(rpath.get_rpath());
+        assert!(short_hash(&rpath) != short_hash(&base));
+        assert!(rpath.args_for_rustc().contains(&"rpath".to_string()));
+    }
+
+    #[test]
+    fn output_dirs() {
+        let dev = Profile::default_dev();
+        let m = manifest(vec![
+            Target::default_lib("foo", &Path::new("src/lib.rs"), &dev, metadata()),
+            Target::bin_target("foo", &Path::new("src/main.rs"), &dev, None)
+                   .out_dir(Some(Path::new("out/bin"))),
+            Target::bin_target("bar", &Path::new("src/bin/bar.rs"), &dev, None)
+                   .out_dir(Some(Path::new("out/bin"))),
+        ]);
+        assert_eq!(m.output_dirs(), vec![Path::new("target"), Path::new("out/bin")]);
+    }
+
+    #[test]
+    fn debug_level() {
+        for profile in [Profile::default_dev(), Profile::default_test(),
+                        Profile::default_bench()].iter() {
+            assert_eq!(profile.get_debug_level(), 2);
+            assert!(profile.get_debug());
+        }
+        assert_eq!(Profile::default_release().get_debug_level(), 0);
+
+        let base = Profile::default_release();
+        let lines = base.clone().debug_level(1);
+        assert!(lines.get_debug());
+        assert!(lines.args_for_rustc().contains(&"debuginfo=1".to_string()));
+        assert!(short_hash(&lines) != short_hash(&base.clone().debug_level(2)));
+        assert_eq!(base.clone().debug(true).get_debug_level(), 2);
+        assert_eq!(base.debug(false).get_debug_level(), 0);
+    }
+
+    #[test]
+    fn debug_assertions_independent_of_debuginfo() {
+        assert!(Profile::default_dev().get_debug_assertions());
+        assert!(Profile::default_test().get_debug_assertions());
+        assert!(!Profile::default_release().get_debug_assertions());
+
+        let release = Profile::default_release().debug(true);
+        assert!(release.get_debug());
+        assert!(!release.get_debug_assertions());
+        assert!(release.args_for_rustc().contains(&"ndebug".to_string()));
+
+        let dev = Profile::default_dev().debug(false);
+        assert!(dev.get_debug_assertions());
+        assert!(!dev.args_for_rustc().contains(&"ndebug".to_string()));
+        assert!(short_hash(&dev) != short_hash(&dev.clone().debug_assertions(false)));
+    }
+
+    #[test]
+    fn debug_assertions_flag() {
+        let dev = Profile::default_dev();
+        let release = Profile::default_release();
+        assert_eq!(dev.debug_assertions_flag().as_slice(), "debug-assertions=on");
+        assert_eq!(release.debug_assertions_flag().as_slice(), "debug-assertions=off");
+
+        let args = dev.args_for_rustc();
+        let pos = args.iter().position(|a| a.as_slice() == "debug-assertions=on").unwrap();
+        assert_eq!(args[pos - 1].as_slice(), "-C");
+        assert!(release.args_for_rustc().contains(&"debug-assertions=off".to_string()));
+
+        let checked = release.clone().debug_assertions(true);
+        assert_eq!(checked.debug_assertions_flag().as_slice(), "debug-assertions=on");
+    }
+
+    #[test]
+    fn overflow_checks() {
+        let defaults = [Profile::default_dev(), Profile::default_test(),
+                        Profile::default_bench(), Profile::default_release(),
+                        Profile::default_doc(), Profile::default_check()];
+        for profile in defaults.iter() {
+            assert_eq!(profile.get_overflow_checks(), None);
+        }
+
+        let base = Profile::default_release();
+        let on = base.clone().overflow_checks(Some(true));
+        let off = base.clone().overflow_checks(Some(false));
+        assert_eq!(on.get_overflow_checks(), Some(true));
+        assert!(short_hash(&on) != short_hash(&base));
+        assert!(short_hash(&on) != short_hash(&off));
+        assert!(on.args_for_rustc().contains(&"overflow-checks=on".to_string()));
+        assert!(!base.args_for_rustc().iter().any(|a| a.as_slice().contains("overflow")));
+    }
+
+    #[test]
+    fn target_buildable() {
+        let triple = "x86_64-unknown-linux-gnu";
+        let dev = Profile::default_dev();
+        let mut features = HashMap::new();
+        features.insert("default".to_string(), vec!["a".to_string()]);
+        features.insert("a".to_string(), vec!["b".to_string()]);
+        features.insert("b".to_string(), Vec::new());
+        features.insert("c".to_string(), Vec::new());
+        let m = manifest_with_features(vec![
+            Target::bin_target("plain", &Path::new("src/main.rs"), &dev, None),
+            Target::bin_target("with-b", &Path::new("src/bin/b.rs"), &dev, None)
+                   .required_features(Some(vec!["b".to_string()])),
+            Target::bin_target("with-c", &Path::new("src/bin/c.rs"), &dev, None)
+                   .required_features(Some(vec!["c".to_string()])),
+        ], Vec::new(), features);
+
+        let targets = m.get_targets();
+        assert!(m.target_buildable(&targets[0], triple));
+        assert!(m.target_buildable(&targets[1], triple));
+        assert!(!m.target_buildable(&targets[2], triple));
+        let names: Vec<&str> = m.default_build_targets(triple).iter().map(|t| {
+            t.get_name()
+        }).collect();
+        assert_eq!(names, vec!["plain", "with-b"]);
+    }
+
+    #[test]
+    fn opt_levels() {
+        for (s, level) in [("0", Num(0)), ("3", Num(3)), ("s", Size), ("z", SizeMin)].iter() {
+            assert_eq!(OptLevel::from_str(*s).unwrap(), *level);
+            assert_eq!(level.to_string().as_slice(), *s);
+        }
+        assert!(OptLevel::from_str("4").is_err());
+        assert!(OptLevel::from_str("fast").is_err());
+
+        let release = Profile::default_release();
+        assert!(release.args_for_rustc().contains(&"3".to_string()));
+        assert!(!Profile::default_dev().args_for_rustc().contains(&"--opt-level".to_string()));
+        let size = release.clone().opt_level(Size);
+        let size_min = release.clone().opt_level(SizeMin);
+        assert!(size.args_for_rustc().contains(&"opt-level=s".to_string()));
+        assert!(size_min.args_for_rustc().contains(&"opt-level=z".to_string()));
+        assert_eq!(size.descriptor().as_slice(), "optimized");
+        assert!(short_hash(&size) != short_hash(&size_min));
+        assert!(short_hash(&size) != short_hash(&release));
+
+        let json = json::encode(&size_min);
+        assert!(json.as_slice().contains("\"opt_level\":\"z\""));
+        let decoded: Profile = json::decode(json.as_slice()).unwrap();
+        assert_eq!(decoded.get_opt_level(), SizeMin);
+    }
+
+    #[test]
+    fn named_profiles() {
+        let mut profiles = HashMap::new();
+        let staging = Profile::default_release().debug(true);
+        profiles.insert("staging".to_string(), staging.clone());
+        let pkg_id = PackageId::new("foo", "1.0.0", &registry()).unwrap();
+        let summary = Summary::new(pkg_id, Vec::new(), HashMap::new()).unwrap();
+        let m = Manifest::new(summary, Vec::new(), Path::new("target"), Path::new("doc"),
+                              Vec::new(), Vec::new(), Vec::new(), profiles);
+
+        assert_eq!(m.get_profile("staging"), Some(&staging));
+        assert_eq!(m.get_profile("release"), None);
+
+        let encoded = json::from_str(json::encode(&m).as_slice()).unwrap();
+        let profiles = encoded.find(&"profiles".to_string()).unwrap().clone();
+        let mut decoder = json::Decoder::new(profiles);
+        let decoded: HashMap<String, Profile> = Decodable::decode(&mut decoder).unwrap();
+        assert_eq!(decoded.find_equiv(&"staging"), Some(&staging));
+    }
+
+    #[test]
+    fn emit_kinds() {
+        let base = Profile::default_release();
+        assert_eq!(base.get_emit(), ["link".to_string(), "dep-info".to_string()].as_slice());
+        assert!(!base.args_for_rustc().iter().any(|a| a.as_slice().starts_with("--emit")));
+
+        let asm = base.clone().emit(vec!["asm".to_string(), "link".to_string()]);
+        assert!(short_hash(&asm) != short_hash(&base));
+        assert!(asm.args_for_rustc().contains(&"--emit=asm,link".to_string()));
+    }
+
+    #[test]
+    fn merge_partial_overrides() {
+        let base = Profile::default_release().cfgs(vec!["foo".to_string()]);
+        let overrides = ProfileOverrides {
+            opt_level: Some(Num(0)),
+            .. Default::default()
+        };
+        let merged = base.merge(&overrides);
+        assert_eq!(merged.get_opt_level(), Num(0));
+        assert_eq!(merged.get_cfgs(), base.get_cfgs());
+        assert_eq!(merged.get_debug_level(), base.get_debug_level());
+        assert_eq!(merged.get_env(), "release");
+        assert_eq!(short_hash(&merged), short_hash(&base.clone().opt_level(Num(0))));
+
+        let unchanged = base.merge(&Default::default());
+        assert_eq!(unchanged, base);
+        assert_eq!(short_hash(&unchanged), short_hash(&base));
+    }
+
+    #[test]
+    fn merge_full_override() {
+        let overrides = ProfileOverrides {
+            opt_level: Some(Num(1)),
+            codegen_units: Some(Some(4)),
+            debug_level: Some(1),
+            debug_assertions: Some(true),
+            overflow_checks: Some(Some(false)),
+            lto: Some(Thin),
+            incremental: Some(true),
+            clippy: Some(false),
+            plugin: Some(false),
+            harness: Some(true),
+            emit: Some(vec!["link".to_string()]),
+            cfgs: Some(vec!["bar".to_string()]),
+            rustflags: Some(vec!["-Zfoo".to_string()]),
+            crt_static: Some(Some(true)),
+            panic: Some(Abort),
+            rpath: Some(true),
+            test_threads: Some(Some(2)),
+        };
+        let expected = Profile::default_dev().opt_level(Num(1)).codegen_units(Some(4))
+            .debug_level(1).debug_assertions(true).overflow_checks(Some(false))
+            .lto_mode(Thin).incremental(true).emit(vec!["link".to_string()])
+            .cfgs(vec!["bar".to_string()]).rustflags(vec!["-Zfoo".to_string()])
+            .crt_static(Some(true)).panic(Abort).rpath(true).test_threads(Some(2));
+        let from_dev = Profile::default_dev().merge(&overrides);
+        let from_release = Profile::default_release().merge(&overrides);
+        assert_eq!(from_dev, expected);
+        assert_eq!(short_hash(&from_dev), short_hash(&from_release));
+    }
+
+    #[test]
+    fn package_ident() {
+        assert_eq!(manifest(Vec::new()).package_ident().as_slice(),
+                   "foo 1.0.0 (registry+https://crates.io/)");
+    }
+
+    #[test]
+    fn example_profiles() {
+        let dev = Profile::default_dev().for_example(false);
+        let release = Profile::default_dev().for_example(true);
+        assert!(!dev.uses_test_harness());
+        assert!(!release.uses_test_harness());
+        assert_eq!(dev.get_env(), "compile");
+        assert_eq!(release.get_env(), "release");
+        assert_eq!(release.get_opt_level(), Num(3));
+        assert!(!release.is_test());
+    }
+
+    #[test]
+    fn build_script_out_dir() {
+        let base = Path::new("/foo/target");
+        assert_eq!(manifest(Vec::new()).build_script_out_dir(&base), None);
+
+        let pkg_id = PackageId::new("foo", "1.0.0", &registry()).unwrap();
+        let summary = Summary::new(pkg_id.clone(), Vec::new(), HashMap::new()).unwrap();
+        let m = Manifest::new(summary, Vec::new(), Path::new("target"), Path::new("doc"),
+                              Vec::new(), vec!["make".to_string()], Vec::new(),
+                              HashMap::new());
+        assert!(m.requires_build_script());
+        assert_eq!(m.build_script_out_dir(&base),
+                   Some(Path::new(format!("/foo/target/native/foo-{}", short_hash(&pkg_id)))));
+    }
+
+    #[test]
+    fn rustc_version_hashed() {
+        let unversioned = Profile::default_dev();
+        let old = Profile::default_dev().rustc_version(Some("0.12.0".to_string()));
+        let new = Profile::default_dev().rustc_version(Some("0.13.0".to_string()));
+        assert_eq!(old.get_rustc_version(), Some("0.12.0"));
+        assert_eq!(unversioned.get_rustc_version(), None);
+        assert!(short_hash(&old) != short_hash(&new));
+        assert!(short_hash(&old) != short_hash(&unversioned));
+        assert_eq!(short_hash(&unversioned), short_hash(&Profile::default_dev()));
+        assert!(old.is_toolchain_changed(&new));
+        assert!(!unversioned.is_toolchain_changed(&Profile::default_dev()));
+    }
+
+    #[test]
+    fn only_target() {
+        let dev = Profile::default_dev();
+        let m = manifest(vec![
+            Target::default_lib("foo", &Path::new("src/lib.rs"), &dev, metadata()),
+            Target::bin_target("a", &Path::new("src/bin/a.rs"), &dev, None),
+            Target::bin_target("b", &Path::new("src/bin/b.rs"), &dev, None),
+        ]);
+
+        let only = m.only_target("a").unwrap();
+        assert_eq!(only.get_targets().len(), 1);
+        assert_eq!(only.get_targets()[0].get_name(), "a");
+        assert_eq!(only.get_name(), "foo");
+        assert_eq!(m.get_targets().len(), 3);
+
+        let err = m.only_target("c").unwrap_err();
+        assert_eq!(err.description().as_slice(),
+                   "no target named `c` in package `foo`");
+    }
+
+    #[test]
+    fn warnings_deduplicated() {
+        let mut m = manifest(Vec::new());
+        m.add_warning("unused manifest key: foo".to_string());
+        m.add_warning("unused manifest key: bar".to_string());
+        m.add_warning("unused manifest key: foo".to_string());
+        assert_eq!(m.get_warnings(), vec!["unused manifest key: foo",
+                                          "unused manifest key: bar"]);
+    }
+
+    #[test]
+    fn env_rustflags_hashed() {
+        let profile = Profile::default_dev().rustflags(vec!["-Zfoo".to_string()]);
+        let env = profile.with_env_rustflags(["-Ctarget-cpu=native".to_string()].as_slice());
+        assert_eq!(env.get_rustflags(), ["-Zfoo".to_string(),
+                                         "-Ctarget-cpu=native".to_string()].as_slice());
+        assert!(short_hash(&profile) != short_hash(&env));
+
+        let unchanged = profile.with_env_rustflags([].as_slice());
+        assert_eq!(short_hash(&profile), short_hash(&unchanged));
+    }
+
+    #[test]
+    fn warning_kinds() {
+        let mut m = manifest(Vec::new());
+        m.add_warning("unused manifest key: foo".to_string());
+        m.add_warning_kind("the [[lib]] section has been deprecated".to_string(),
+                           Deprecation);
+        assert_eq!(m.get_warnings(), vec!["unused manifest key: foo",
+                                          "the [[lib]] section has been deprecated"]);
+        assert_eq!(m.get_detailed_warnings(), [
+            ManifestWarning { message: "unused manifest key: foo".to_string(),
+                              kind: Unused },
+            ManifestWarning { message: "the [[lib]] section has been deprecated".to_string(),
+                              kind: Deprecation },
+        ].as_slice());
+    }
+
+    #[test]
+    fn version_stability() {
+        let stability = |version: &str| {
+            let pkg_id = PackageId::new("foo", version, &registry()).unwrap();
+            let summary = Summary::new(pkg_id, Vec::new(), HashMap::new()).unwrap();
+            Manifest::new(summary, Vec::new(), Path::new("target"), Path::new("doc"),
+                          Vec::new(), Vec::new(), Vec::new(),
+                          HashMap::new()).version_stability()
+        };
+        assert_eq!(stability("0.1.0"), Unstable);
+        assert_eq!(stability("1.0.0"), Stable);
+        assert_eq!(stability("2.3.1"), Stable);
+        assert_eq!(stability("1.0.0-beta"), PreRelease);
+        assert_eq!(stability("0.3.0-alpha.1"), PreRelease);
+    }
+
+    #[test]
+    fn exclude_globs() {
+        let mut m = manifest(Vec::new());
+        m.exclude = vec!["*.tmp".to_string(), "target/".to_string(),
+                         "/docs/private".to_string(), "build/**/*.o".to_string()];
+
+        assert!(m.is_excluded(&Path::new("foo.tmp")));
+        assert!(m.is_excluded(&Path::new("src/foo.tmp")));
+        assert!(!m.is_excluded(&Path::new("src/foo.rs")));
+
+        assert!(m.is_excluded(&Path::new("target/debug/foo")));
+        assert!(m.is_excluded(&Path::new("examples/target/foo")));
+        assert!(!m.is_excluded(&Path::new("target")));
+
+        assert!(m.is_excluded(&Path::new("docs/private/notes.md")));
+        assert!(!m.is_excluded(&Path::new("src/docs/private/notes.md")));
+        assert!(m.is_excluded(&Path::new("build/x86/foo.o")));
+
+        assert!(m.should_package(&Path::new("src/lib.rs")));
+        assert!(!m.should_package(&Path::new("src/foo.tmp")));
+    }
+
+    #[test]
+    fn include_overrides_exclude() {
+        let mut m = manifest(Vec::new());
+        m.exclude = vec!["*.tmp".to_string(), "src/".to_string()];
+        m.set_include(vec!["src/**/*".to_string(), "Cargo.toml".to_string()]);
+
+        assert!(m.should_package(&Path::new("Cargo.toml")));
+        assert!(m.should_package(&Path::new("src/lib.rs")));
+        assert!(m.should_package(&Path::new("src/foo.tmp")));
+        assert!(!m.should_package(&Path::new("README.md")));
+        assert!(!m.should_package(&Path::new("tests/foo.rs")));
+        assert!(m.is_excluded(&Path::new("src/lib.rs")));
+    }
+
+    #[test]
+    fn find_dependency() {
+        let m = manifest_with_deps(Vec::new(), vec![
+            Dependency::parse("bar", Some("1.0.0"), &registry()).unwrap(),
+            Dependency::parse("baz", Some("0.2.0"), &registry()).unwrap(),
+        ]);
+        assert_eq!(m.find_dependency("baz").map(|d| d.get_name()), Some("baz"));
+        assert!(m.has_dependency("bar"));
+        assert!(m.find_dependency("qux").is_none());
+        assert!(!m.has_dependency("Bar"));
+
+        let m = manifest(Vec::new());
+        assert!(m.find_dependency("bar").is_none());
+        assert!(!m.has_dependency("bar"));
+    }
+
+    #[test]
+    fn fastest_compile() {
+        let fastest = Profile::fastest_compile();
+        assert_eq!(fastest.get_opt_level(), Num(0));
+        assert!(!fastest.get_debug());
+        assert_eq!(fastest.get_codegen_units(), Some(256));
+        assert!(fastest.is_incremental());
+        assert!(fastest.is_compile());
+        assert!(fastest.validate().is_ok());
+        assert!(fastest.validate_incremental_lto().is_ok());
+        assert!(short_hash(&fastest) != short_hash(&Profile::default_dev()));
+    }
+}
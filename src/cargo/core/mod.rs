@@ -1,11 +1,12 @@
 pub use self::dependency::Dependency;
-pub use self::manifest::{Manifest, Target, TargetKind, Profile};
+pub use self::manifest::{Manifest, Target, TargetKind, Profile, ProfileOverrides};
+pub use self::manifest::{ManifestWarning, WarningKind, VersionStability};
 pub use self::package::{Package, PackageSet};
 pub use self::package_id::PackageId;
 pub use self::package_id_spec::PackageIdSpec;
 pub use self::registry::Registry;
 pub use self::resolver::Resolve;
-pub use self::shell::{Shell, MultiShell, ShellConfig};
+pub use self::shell::{Shell, MultiShell, ShellConfig, ColorConfig};
 pub use self::source::{PathKind, RegistryKind};
 pub use self::source::{Source, SourceId, SourceMap, SourceSet, GitKind};
 pub use self::summary::Summary;
@@ -1,13 +1,51 @@
 use term::{mod, Terminal, color};
-use term::color::{Color, BLACK, RED, GREEN, YELLOW};
-use term::attr::{Attr, Bold};
-use std::io::{IoResult, stderr};
+use term::color::{Color, BLACK, RED, GREEN, YELLOW, CYAN};
+use term::attr::{Attr, Bold, Dim};
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::HashSet;
+use std::io::{mod, IoError, IoResult};
 use std::fmt::Show;
+use std::rc::Rc;
+
+use semver::Version;
+use serialize::json;
+
+use core::Manifest;
+use util::CargoError;
+
+// The width of the right-aligned status column of `say_status`.
+static STATUS_WIDTH: uint = 12;
+
+/// When a shell should write color escapes.
+#[deriving(Show, Clone, PartialEq)]
+pub enum ColorConfig {
+    Auto, // only when writing to a tty or a known ANSI-capable environment
+    Always,
+    Never,
+}
+
+/// How messages meant for other programs, such as timings, are rendered.
+#[deriving(Show, Clone, PartialEq)]
+pub enum MessageFormat {
+    Human,
+    Json, // one JSON object per line
+}
+
+#[deriving(Encodable)]
+struct TimingInfo<'a> {
+    reason: &'static str,
+    target: &'a str,
+    phase: &'a str,
+    duration: f64,
+}
 
 pub struct ShellConfig {
-    pub color: bool,
+    pub color: ColorConfig,
     pub verbose: bool,
-    pub tty: bool
+    pub tty: bool,
+    pub width: Option<uint>, // terminal width in columns, if known
+    pub bell: bool, // whether `alert` may ring the terminal bell
 }
 
 enum AdequateTerminal<'a> {
@@ -17,20 +55,39 @@ enum AdequateTerminal<'a> {
 
 pub struct Shell<'a> {
     terminal: AdequateTerminal<'a>,
-    config: ShellConfig
+    config: ShellConfig,
+    // whether the most recent tree node printed at each depth was the last of
+    // its siblings, used to draw continuation bars in `say_tree_line`
+    tree: Vec<bool>,
+    // whether the last byte written was a newline (or nothing was written)
+    at_line_start: bool,
+    // the length of the progress bar currently drawn, 0 if there is none
+    progress_len: uint,
 }
 
 pub struct MultiShell<'a> {
     out: Shell<'a>,
     err: Shell<'a>,
-    verbose: bool
+    verbose: bool,
+    quiet: bool, // suppress normal output, but not warnings and errors
+    deprecations: HashSet<String>,
+    forced_color_noted: bool,
+    message_format: MessageFormat,
 }
 
 pub type Callback<'a> = |&mut MultiShell|:'a -> IoResult<()>;
 
 impl<'a> MultiShell<'a> {
     pub fn new(out: Shell<'a>, err: Shell<'a>, verbose: bool) -> MultiShell<'a> {
-        MultiShell { out: out, err: err, verbose: verbose }
+        MultiShell {
+            out: out,
+            err: err,
+            verbose: verbose,
+            quiet: false,
+            deprecations: HashSet::new(),
+            forced_color_noted: false,
+            message_format: Human,
+        }
     }
 
     pub fn out(&mut self) -> &mut Shell<'a> {
@@ -42,11 +99,18 @@ impl<'a> MultiShell<'a> {
     }
 
     pub fn say<T: ToString>(&mut self, message: T, color: Color) -> IoResult<()> {
+        if self.quiet { return Ok(()) }
         self.out().say(message, color)
     }
 
     pub fn status<T: Show, U: Show>(&mut self, status: T, message: U) -> IoResult<()> {
-        self.out().say_status(status, message, GREEN)
+        self.status_with_color(status, message, GREEN)
+    }
+
+    pub fn status_with_color<T: Show, U: Show>(&mut self, status: T, message: U,
+                                               color: Color) -> IoResult<()> {
+        if self.quiet { return Ok(()) }
+        self.out().say_status(status, message, color)
     }
 
     pub fn verbose(&mut self, callback: Callback) -> IoResult<()> {
@@ -63,32 +127,263 @@ impl<'a> MultiShell<'a> {
         self.err().say(message, RED)
     }
 
+    /// Prints an error followed by its chain of causes, numbering each cause
+    /// rather than nesting it.
+    pub fn print_numbered_causes(&mut self, err: &CargoError) -> IoResult<()> {
+        try!(self.error(err.description()));
+        let mut cause = err.cause();
+        let mut i = 1u;
+        loop {
+            let e = match cause { Some(e) => e, None => break };
+            let shell = self.err();
+            try!(shell.reset());
+            if shell.supports_attr(Dim) { try!(shell.attr(Dim)); }
+            try!(shell.write_str(format!("  {}: ", i).as_slice()));
+            try!(shell.reset());
+            try!(shell.write_line(e.description().as_slice()));
+            cause = e.cause();
+            i += 1;
+        }
+        self.err().flush()
+    }
+
     pub fn warn<T: ToString>(&mut self, message: T) -> IoResult<()> {
         self.err().say(message, YELLOW)
     }
 
+    /// Prints `message` followed by `command` on a line of its own, in bold
+    /// cyan, so that it's easy to spot and copy.
+    pub fn suggest(&mut self, message: &str, command: &str) -> IoResult<()> {
+        let err = self.err();
+        try!(err.say(message, BLACK));
+        try!(err.reset());
+        try!(err.write_str("    "));
+        try!(err.fg(CYAN));
+        if err.supports_attr(Bold) { try!(err.attr(Bold)); }
+        try!(err.write_str(command));
+        try!(err.reset());
+        try!(err.write_str("\n"));
+        err.flush()
+    }
+
+    /// Prints an informational message to stderr, prefixed with a cyan
+    /// `note:`. Like `status`, nothing is printed in quiet mode.
+    pub fn note<T: ToString>(&mut self, message: T) -> IoResult<()> {
+        if self.quiet { return Ok(()) }
+        let err = self.err();
+        try!(err.reset());
+        try!(err.fg(CYAN));
+        try!(err.write_str("note:"));
+        try!(err.reset());
+        try!(err.write_line(format!(" {}", message.to_string()).as_slice()));
+        try!(err.flush());
+        Ok(())
+    }
+
+    pub fn warn_with_code(&mut self, code: &str, message: &str) -> IoResult<()> {
+        let err = self.err();
+        try!(err.reset());
+        try!(err.fg(YELLOW));
+        try!(err.write_str(format!("warning[{}]", code).as_slice()));
+        try!(err.reset());
+        try!(err.write_line(format!(": {}", message).as_slice()));
+        try!(err.flush());
+        Ok(())
+    }
+
+    /// Prints `(category, message)` warnings with those of each category
+    /// grouped under a single header, in the order the categories first
+    /// appear.
+    pub fn print_grouped_warnings(&mut self,
+                                  warnings: &[(String, String)]) -> IoResult<()> {
+        let mut categories: Vec<&str> = Vec::new();
+        for &(ref category, _) in warnings.iter() {
+            if !categories.contains(&category.as_slice()) {
+                categories.push(category.as_slice());
+            }
+        }
+        for category in categories.iter() {
+            try!(self.warn(format!("warning: {}", category)));
+            let err = self.err();
+            for &(ref c, ref message) in warnings.iter() {
+                if c.as_slice() != *category { continue }
+                try!(err.write_line(format!("    {}", message).as_slice()));
+            }
+        }
+        self.err().flush()
+    }
+
+    /// Explains that `package` is required with two conflicting version
+    /// requirements, which are highlighted.
+    pub fn print_conflict(&mut self, package: &str, req_a: &str,
+                          req_b: &str) -> IoResult<()> {
+        let err = self.err();
+        try!(err.reset());
+        try!(err.write_str(format!("{} required at both ", package).as_slice()));
+        try!(err.fg(YELLOW));
+        try!(err.write_str(req_a));
+        try!(err.reset());
+        try!(err.write_str(" and "));
+        try!(err.fg(YELLOW));
+        try!(err.write_str(req_b));
+        try!(err.reset());
+        try!(err.write_str("\n"));
+        err.flush()
+    }
+
+    /// Prints a deprecation warning, unless one was already printed for `key`
+    /// during this session.
+    pub fn deprecated_once(&mut self, key: &str, message: &str) -> IoResult<()> {
+        if !self.deprecations.insert(key.to_string()) { return Ok(()) }
+        self.warn(message)
+    }
+
+    /// Notes, once per session, that color escapes are being written to a
+    /// stream which isn't a terminal, as that is usually unintended.
+    pub fn warn_forced_color_once(&mut self) -> IoResult<()> {
+        if self.forced_color_noted { return Ok(()) }
+        if !self.out.is_color_forced() && !self.err.is_color_forced() {
+            return Ok(())
+        }
+        self.forced_color_noted = true;
+        self.err().say("note: color output was forced, but output is not a \
+                        terminal", BLACK)
+    }
+
+    /// Asks the user to pick one of `options`, returning its index. This
+    /// requires stderr to be a tty.
+    pub fn select(&mut self, prompt: &str, options: &[String]) -> IoResult<uint> {
+        if !self.err.config.tty {
+            return Err(IoError {
+                kind: io::OtherIoError,
+                desc: "cannot prompt for a selection without a tty",
+                detail: None,
+            })
+        }
+        self.select_from(prompt, options, &mut io::stdin())
+    }
+
+    /// Like `select`, but reads the answer from `input`. Invalid answers
+    /// cause the prompt to be repeated.
+    pub fn select_from<B: Buffer>(&mut self, prompt: &str, options: &[String],
+                                  input: &mut B) -> IoResult<uint> {
+        if options.len() == 0 {
+            return Err(IoError {
+                kind: io::InvalidInput,
+                desc: "no options to select from",
+                detail: None,
+            })
+        }
+        loop {
+            try!(self.err().say(prompt, BLACK));
+            for (i, option) in options.iter().enumerate() {
+                try!(self.err().say(format!("  {}) {}", i + 1, option), BLACK));
+            }
+            let line = try!(input.read_line());
+            let line = line.as_slice().trim();
+            match from_str::<uint>(line) {
+                Some(n) if n >= 1 && n <= options.len() => return Ok(n - 1),
+                _ => try!(self.warn(format!("`{}` is not one of the listed \
+                                             options", line))),
+            }
+        }
+    }
+
     pub fn set_verbose(&mut self, verbose: bool) {
         self.verbose = verbose;
     }
+
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    pub fn set_message_format(&mut self, format: MessageFormat) {
+        self.message_format = format;
+    }
+
+    /// Reports how long `phase` of building `target` took, as a
+    /// `timing-info` JSON line. Nothing is printed in human mode.
+    pub fn emit_timing(&mut self, target: &str, phase: &str,
+                       duration_secs: f64) -> IoResult<()> {
+        if self.message_format != Json { return Ok(()) }
+        let line = json::encode(&TimingInfo {
+            reason: "timing-info",
+            target: target,
+            phase: phase,
+            duration: duration_secs,
+        });
+        let out = self.out();
+        try!(out.write_line(line.as_slice()));
+        out.flush()
+    }
 }
 
 pub type ShellCallback<'a> = |&mut Shell<'a>|:'a -> IoResult<()>;
 
 impl<'a> Shell<'a> {
     pub fn create(out: Box<Writer+'a>, config: ShellConfig) -> Shell<'a> {
-        if config.tty && config.color {
-            let term: Option<term::TerminfoTerminal<Box<Writer+'a>>> = Terminal::new(out);
-            term.map(|t| Shell {
-                terminal: Colored(box t as Box<Terminal<Box<Writer+'a>>>),
-                config: config
-            }).unwrap_or_else(|| {
-                Shell { terminal: NoColor(box stderr() as Box<Writer+'a>), config: config }
-            })
+        Shell::create_with_env(out, config, |_| None)
+    }
+
+    /// Like `create`, but looks up environment variables through `env`. Some
+    /// environments, such as CI services, render color escapes even though
+    /// the output isn't a tty, so color is enabled there too.
+    pub fn create_with_env(out: Box<Writer+'a>, config: ShellConfig,
+                           env: |&str| -> Option<String>) -> Shell<'a> {
+        let colorize = match config.color {
+            Always => true,
+            Never => false,
+            Auto => config.tty || ansi_environment(env),
+        };
+        let terminal = if colorize {
+            // Keep a handle on the output, so that it can still be written
+            // to plainly if there's no usable terminfo entry for $TERM.
+            let out = Rc::new(RefCell::new(out));
+            let shared = box SharedWriter(out.clone()) as Box<Writer+'a>;
+            let term: Option<term::TerminfoTerminal<Box<Writer+'a>>> = Terminal::new(shared);
+            match term {
+                Some(t) => Colored(box t as Box<Terminal<Box<Writer+'a>>>),
+                None => NoColor(box SharedWriter(out) as Box<Writer+'a>),
+            }
         } else {
-            Shell { terminal: NoColor(out), config: config }
+            NoColor(out)
+        };
+        Shell {
+            terminal: terminal,
+            config: config,
+            tree: Vec::new(),
+            at_line_start: true,
+            progress_len: 0,
+        }
+    }
+
+    /// Renders a byte count using binary units, e.g. `1.5 KiB`.
+    pub fn format_bytes(n: u64) -> String {
+        static UNITS: [&'static str, ..4] = ["KiB", "MiB", "GiB", "TiB"];
+        if n < 1024 { return format!("{} B", n) }
+
+        let mut size = n as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+
+    fn is_colored(&self) -> bool {
+        match self.terminal {
+            Colored(..) => true,
+            NoColor(..) => false,
         }
     }
 
+    /// Whether this shell writes color escapes even though its output isn't
+    /// a tty.
+    pub fn is_color_forced(&self) -> bool {
+        self.is_colored() && !self.config.tty
+    }
+
     pub fn verbose(&mut self, callback: ShellCallback) -> IoResult<()> {
         if self.config.verbose { return callback(self) }
         Ok(())
@@ -100,6 +395,7 @@ impl<'a> Shell<'a> {
     }
 
     pub fn say<T: ToString>(&mut self, message: T, color: Color) -> IoResult<()> {
+        try!(self.clear_progress());
         try!(self.reset());
         if color != BLACK { try!(self.fg(color)); }
         try!(self.write_line(message.to_string().as_slice()));
@@ -110,15 +406,399 @@ impl<'a> Shell<'a> {
 
     pub fn say_status<T: Show, U: Show>(&mut self, status: T, message: U,
                                         color: Color) -> IoResult<()> {
+        self.say_status_with(status, message, color, STATUS_WIDTH)
+    }
+
+    /// Like `say_status`, but right-aligns `status` in a column of `width`
+    /// characters. Statuses longer than that are printed as they are.
+    pub fn say_status_with<T: Show, U: Show>(&mut self, status: T, message: U,
+                                             color: Color,
+                                             width: uint) -> IoResult<()> {
+        let mut status = status.to_string();
+        let len = status.as_slice().char_len();
+        if len < width {
+            status = format!("{}{}", String::from_char(width - len, ' '), status);
+        }
+        try!(self.clear_progress());
         try!(self.reset());
         if color != BLACK { try!(self.fg(color)); }
         if self.supports_attr(Bold) { try!(self.attr(Bold)); }
-        try!(self.write_str(format!("{:>12}", status).as_slice()));
+        try!(self.write_str(status.as_slice()));
         try!(self.reset());
+        let status_width = status.as_slice().char_len();
+        let message = self.wrap_from(message.to_string().as_slice(), status_width + 1,
+                                     status_width);
         try!(self.write_line(format!(" {}", message).as_slice()));
         try!(self.flush());
         Ok(())
     }
+
+    /// Wraps the lines of `msg` which don't fit the terminal at word
+    /// boundaries. Continuation lines are indented to line up with the
+    /// message of a `say_status` line. Without a known width nothing is
+    /// wrapped.
+    pub fn wrap(&self, msg: &str) -> String {
+        self.wrap_from(msg, 0, STATUS_WIDTH)
+    }
+
+    // Like `wrap`, for a message starting at column `start`, with
+    // continuation lines lined up after a status of `status_width`.
+    fn wrap_from(&self, msg: &str, start: uint, status_width: uint) -> String {
+        let width = match self.config.width {
+            Some(width) => width,
+            None => return msg.to_string(),
+        };
+        let indent = String::from_char(status_width + 1, ' ');
+        let mut lines = Vec::new();
+        for (i, line) in msg.split('\n').enumerate() {
+            let mut col = if i == 0 {start} else {0};
+            if col + line.char_len() <= width {
+                lines.push(line.to_string());
+                continue
+            }
+            // Splitting on single spaces keeps runs of whitespace, such as
+            // indentation, intact. Lines are only broken between words.
+            let mut wrapped = String::new();
+            let mut has_word = false;
+            for (j, word) in line.split(' ').enumerate() {
+                if j > 0 && has_word && word.len() > 0 &&
+                   col + 1 + word.char_len() > width {
+                    let len = wrapped.as_slice().trim_right_chars(' ').len();
+                    wrapped.truncate(len);
+                    wrapped.push('\n');
+                    wrapped.push_str(indent.as_slice());
+                    col = indent.len();
+                    has_word = false;
+                } else if j > 0 {
+                    wrapped.push(' ');
+                    col += 1;
+                }
+                wrapped.push_str(word);
+                col += word.char_len();
+                has_word = has_word || word.len() > 0;
+            }
+            lines.push(wrapped);
+        }
+        lines.connect("\n")
+    }
+
+    /// Rings the terminal bell, e.g. when a long build finishes. This only
+    /// happens on a tty and when enabled in the config.
+    pub fn alert(&mut self) -> IoResult<()> {
+        if !self.config.tty || !self.config.bell { return Ok(()) }
+        try!(self.write_str("\x07"));
+        self.flush()
+    }
+
+    /// Prints a message which the next line of output will overwrite, for
+    /// simple live status updates. Piped output gets a normal line instead.
+    pub fn say_inline(&mut self, message: &str) -> IoResult<()> {
+        if self.config.tty {
+            try!(self.write_str(message));
+            try!(self.write_str("\r"));
+        } else {
+            try!(self.write_line(message));
+        }
+        self.flush()
+    }
+
+    /// Ends the current line, unless the previous output already did. Useful
+    /// after `say_inline` before printing the next block of output.
+    pub fn ensure_newline(&mut self) -> IoResult<()> {
+        if self.at_line_start { return Ok(()) }
+        try!(self.write_str("\n"));
+        self.flush()
+    }
+
+    /// Prints `message` with the given byte ranges highlighted, e.g. to show
+    /// search matches. Ranges are clamped to the message, overlapping parts
+    /// are only highlighted once, and ranges which don't fall on character
+    /// boundaries are ignored. Without color the highlighted text is wrapped
+    /// in `>>` and `<<` instead.
+    pub fn say_highlighted(&mut self, message: &str,
+                           ranges: &[(uint, uint)]) -> IoResult<()> {
+        let colored = self.is_colored();
+        let mut ranges = ranges.to_vec();
+        ranges.sort();
+        let mut pos = 0;
+        try!(self.reset());
+        for &(start, end) in ranges.iter() {
+            let start = cmp::max(start, pos);
+            let end = cmp::min(end, message.len());
+            if start >= end || !message.is_char_boundary(start) ||
+               !message.is_char_boundary(end) {
+                continue
+            }
+            try!(self.write_str(message.slice(pos, start)));
+            if colored {
+                try!(self.bg(YELLOW));
+                try!(self.write_str(message.slice(start, end)));
+                try!(self.reset());
+            } else {
+                try!(self.write_str(format!(">>{}<<",
+                                            message.slice(start, end)).as_slice()));
+            }
+            pos = end;
+        }
+        try!(self.write_line(message.slice_from(pos)));
+        self.flush()
+    }
+
+    /// Prints `label` preceded by a green `✓` or a red `✗`. Without color the
+    /// markers are `[ok]` and `[FAIL]` instead.
+    pub fn say_result(&mut self, passed: bool, label: &str) -> IoResult<()> {
+        try!(self.reset());
+        if self.is_colored() {
+            let (marker, color) = if passed {("✓", GREEN)} else {("✗", RED)};
+            try!(self.fg(color));
+            try!(self.write_str(marker));
+            try!(self.reset());
+        } else {
+            try!(self.write_str(if passed {"[ok]"} else {"[FAIL]"}));
+        }
+        try!(self.write_line(format!(" {}", label).as_slice()));
+        self.flush()
+    }
+
+    /// Prints one feature per line, marked with `+` if it's active and `-`
+    /// otherwise. With color, active features are green and inactive ones dim.
+    pub fn say_features(&mut self, features: &[(String, bool)]) -> IoResult<()> {
+        for &(ref name, active) in features.iter() {
+            try!(self.reset());
+            if self.is_colored() {
+                if active {
+                    try!(self.fg(GREEN));
+                } else if self.supports_attr(Dim) {
+                    try!(self.attr(Dim));
+                }
+            }
+            let marker = if active {'+'} else {'-'};
+            try!(self.write_str(format!("{} {}", marker, name).as_slice()));
+            try!(self.reset());
+            try!(self.write_str("\n"));
+        }
+        self.flush()
+    }
+
+    /// Prints a separator line of `ch` spanning the terminal, or 40 columns
+    /// if the width of the terminal isn't known.
+    pub fn say_rule(&mut self, ch: char) -> IoResult<()> {
+        let width = self.config.width.unwrap_or(40);
+        let line = String::from_char(width, ch);
+        try!(self.reset());
+        if self.supports_attr(Dim) { try!(self.attr(Dim)); }
+        try!(self.write_line(line.as_slice()));
+        try!(self.reset());
+        self.flush()
+    }
+
+    /// Shows how far along some work is as a percentage, updated in place.
+    /// Nothing is printed when the output isn't a tty.
+    pub fn say_percent(&mut self, done: uint, total: uint,
+                       msg: &str) -> IoResult<()> {
+        if !self.config.tty { return Ok(()) }
+        let percent = if total == 0 {
+            100
+        } else {
+            cmp::min((done * 100 + total / 2) / total, 100)
+        };
+        let mut line = format!("{}% {}", percent, msg);
+        // cover up what's left of a longer previous line
+        let len = line.as_slice().char_len();
+        if len < self.progress_len {
+            line.push_str(String::from_char(self.progress_len - len, ' ').as_slice());
+        }
+        try!(self.say_inline(line.as_slice()));
+        self.progress_len = line.as_slice().char_len();
+        Ok(())
+    }
+
+    /// Draws a bar showing `done` out of `total` steps followed by `msg`,
+    /// redrawn in place on each call. Nothing is printed when the output
+    /// isn't a tty, so piped logs stay clean.
+    pub fn progress(&mut self, done: uint, total: uint,
+                    msg: &str) -> IoResult<()> {
+        if !self.config.tty { return Ok(()) }
+        static BAR_WIDTH: uint = 20;
+        let filled = if total == 0 {
+            BAR_WIDTH
+        } else {
+            cmp::min(done, total) * BAR_WIDTH / total
+        };
+        let line = format!("[{}{}] {}/{} {}",
+                           String::from_char(filled, '='),
+                           String::from_char(BAR_WIDTH - filled, ' '),
+                           done, total, msg);
+        try!(self.clear_progress());
+        try!(self.say_inline(line.as_slice()));
+        self.progress_len = line.as_slice().char_len();
+        Ok(())
+    }
+
+    /// Erases the bar drawn by `progress`, if any, leaving the cursor at the
+    /// start of the now empty line.
+    pub fn clear_progress(&mut self) -> IoResult<()> {
+        if self.progress_len == 0 { return Ok(()) }
+        let blank = String::from_char(self.progress_len, ' ');
+        self.progress_len = 0;
+        try!(self.write_str(blank.as_slice()));
+        try!(self.write_str("\r"));
+        self.at_line_start = true;
+        self.flush()
+    }
+
+    /// Reports progress to terminals which show it in the taskbar or tab
+    /// (Windows Terminal, ConEmu) via the OSC 9;4 escape sequence.
+    pub fn set_terminal_progress(&mut self, percent: u8) -> IoResult<()> {
+        if !self.config.tty { return Ok(()) }
+        let percent = cmp::min(percent, 100);
+        try!(self.write_str(format!("\x1b]9;4;1;{}\x07", percent).as_slice()));
+        self.flush()
+    }
+
+    pub fn clear_terminal_progress(&mut self) -> IoResult<()> {
+        if !self.config.tty { return Ok(()) }
+        try!(self.write_str("\x1b]9;4;0\x07"));
+        self.flush()
+    }
+
+    /// Notes that `latest` of `name` is available, if it is newer than
+    /// `current`. Nothing is printed otherwise.
+    pub fn say_upgrade_available(&mut self, name: &str, current: &Version,
+                                 latest: &Version) -> IoResult<()> {
+        if latest <= current { return Ok(()) }
+        self.say(format!("{} {} is available (currently {})", name, latest,
+                         current), YELLOW)
+    }
+
+    /// Prints `name: from -> to`, highlighting the most significant component
+    /// of the version which changed.
+    pub fn say_version_bump(&mut self, name: &str, from: &Version,
+                            to: &Version) -> IoResult<()> {
+        let changed = if from.major != to.major {
+            0u
+        } else if from.minor != to.minor {
+            1
+        } else if from.patch != to.patch {
+            2
+        } else {
+            3
+        };
+        let parts = [to.major, to.minor, to.patch];
+        let full = to.to_string();
+        let base_len = format!("{}.{}.{}", to.major, to.minor, to.patch).len();
+
+        try!(self.reset());
+        try!(self.write_str(format!("{}: {} -> ", name, from).as_slice()));
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 { try!(self.write_str(".")); }
+            if i == changed { try!(self.fg(GREEN)); }
+            try!(self.write_str(part.to_string().as_slice()));
+            if i == changed { try!(self.reset()); }
+        }
+        if changed == 3 { try!(self.fg(GREEN)); }
+        try!(self.write_str(full.as_slice().slice_from(base_len)));
+        try!(self.reset());
+        try!(self.write_str("\n"));
+        try!(self.flush());
+        Ok(())
+    }
+
+    /// Prints a one-line summary of a manifest: `name vX.Y.Z (N targets)`.
+    pub fn say_manifest(&mut self, m: &Manifest) -> IoResult<()> {
+        let targets = m.get_targets().len();
+        try!(self.reset());
+        if self.supports_attr(Bold) { try!(self.attr(Bold)); }
+        try!(self.write_str(m.get_name()));
+        try!(self.reset());
+        try!(self.write_str(format!(" v{} ({} target{})\n", m.get_version(),
+                                    targets,
+                                    if targets == 1 {""} else {"s"}).as_slice()));
+        self.flush()
+    }
+
+    /// Prints `name req` for a dependency, with the comparison operators of
+    /// the version requirement dimmed and the versions emphasized.
+    pub fn say_version_req(&mut self, name: &str, req: &str) -> IoResult<()> {
+        try!(self.reset());
+        try!(self.write_str(format!("{} ", name).as_slice()));
+        for (i, predicate) in req.split(',').enumerate() {
+            if i > 0 { try!(self.write_str(",")); }
+            let split = predicate.find(|c: char| {
+                !(c.is_whitespace() || "^~<>=".contains_char(c))
+            }).unwrap_or(predicate.len());
+            let (op, version) = (predicate.slice_to(split),
+                                 predicate.slice_from(split));
+            if self.supports_attr(Dim) { try!(self.attr(Dim)); }
+            try!(self.write_str(op));
+            try!(self.reset());
+            if self.supports_attr(Bold) { try!(self.attr(Bold)); }
+            try!(self.write_str(version));
+            try!(self.reset());
+        }
+        try!(self.write_str("\n"));
+        self.flush()
+    }
+
+    /// Prints a unified diff, coloring added and removed lines and hunk
+    /// headers.
+    pub fn say_diff(&mut self, diff: &str) -> IoResult<()> {
+        for line in diff.lines() {
+            let color = if line.starts_with("@@") {
+                CYAN
+            } else if line.starts_with("+") {
+                GREEN
+            } else if line.starts_with("-") {
+                RED
+            } else {
+                BLACK
+            };
+            try!(self.say(line, color));
+        }
+        Ok(())
+    }
+
+    /// Prints one node of a tree, with `depth` 0 being the root. Nodes must be
+    /// printed in depth-first order so the continuation bars line up.
+    pub fn say_tree_line(&mut self, depth: uint, is_last: bool,
+                         text: &str) -> IoResult<()> {
+        self.tree.truncate(depth);
+        while self.tree.len() < depth { self.tree.push(true); }
+
+        let mut line = String::new();
+        if depth > 0 {
+            for &last in self.tree.slice_from(1).iter() {
+                line.push_str(if last {"    "} else {"│   "});
+            }
+            line.push_str(if is_last {"└── "} else {"├── "});
+        }
+        line.push_str(text);
+        self.tree.push(is_last);
+
+        self.say(line, BLACK)
+    }
+}
+
+fn ansi_environment(env: |&str| -> Option<String>) -> bool {
+    let term = env("TERM");
+    if term.as_ref().map(|t| t.as_slice()) == Some("dumb") { return false }
+    env("GITHUB_ACTIONS").is_some() || (env("CI").is_some() && term.is_some())
+}
+
+// A writer which can be handed to a terminal while still being reachable
+// from elsewhere.
+struct SharedWriter<'a>(Rc<RefCell<Box<Writer+'a>>>);
+
+impl<'a> Writer for SharedWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        let SharedWriter(ref out) = *self;
+        out.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        let SharedWriter(ref out) = *self;
+        out.borrow_mut().flush()
+    }
 }
 
 impl<'a> Terminal<Box<Writer+'a>> for Shell<'a> {
@@ -126,10 +806,15 @@ impl<'a> Terminal<Box<Writer+'a>> for Shell<'a> {
         Some(Shell {
             terminal: NoColor(out),
             config: ShellConfig {
-                color: true,
+                color: Auto,
                 verbose: false,
                 tty: false,
-            }
+                width: None,
+                bell: false,
+            },
+            tree: Vec::new(),
+            at_line_start: true,
+            progress_len: 0,
         })
     }
 
@@ -189,6 +874,9 @@ impl<'a> Terminal<Box<Writer+'a>> for Shell<'a> {
 
 impl<'a> Writer for Shell<'a> {
     fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        if buf.len() > 0 {
+            self.at_line_start = buf[buf.len() - 1] == b'\n';
+        }
         match self.terminal {
             Colored(ref mut c) => c.write(buf),
             NoColor(ref mut n) => n.write(buf)
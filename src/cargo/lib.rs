@@ -32,6 +32,7 @@ use serialize::{Decoder, Encoder, Decodable, Encodable, json};
 use docopt::FlagParser;
 
 use core::{Shell, MultiShell, ShellConfig};
+use core::shell::Auto;
 use term::color::{BLACK};
 
 pub use util::{CargoError, CliError, CliResult, human};
@@ -153,19 +154,35 @@ pub fn process_executed<'a,
 }
 
 pub fn shell(verbose: bool) -> MultiShell<'static> {
+    // Only wrap output for terminals, falling back to $COLUMNS when the
+    // terminal can't tell its own width
+    let width = |tty: bool, fd: libc::c_int| {
+        if !tty { return None }
+        util::term_width(fd).or_else(|| {
+            os::getenv("COLUMNS").and_then(|c| from_str::<uint>(c.as_slice()))
+        })
+    };
+
     let tty = stderr_raw().isatty();
     let stderr = box stderr() as Box<Writer>;
 
-    let config = ShellConfig { color: true, verbose: verbose, tty: tty };
-    let err = Shell::create(stderr, config);
+    let config = ShellConfig {
+        color: Auto, verbose: verbose, tty: tty, width: width(tty, 2), bell: false,
+    };
+    let err = Shell::create_with_env(stderr, config, |key| os::getenv(key));
 
     let tty = stdout_raw().isatty();
     let stdout = box stdout() as Box<Writer>;
 
-    let config = ShellConfig { color: true, verbose: verbose, tty: tty };
-    let out = Shell::create(stdout, config);
+    let config = ShellConfig {
+        color: Auto, verbose: verbose, tty: tty, width: width(tty, 1), bell: false,
+    };
+    let out = Shell::create_with_env(stdout, config, |key| os::getenv(key));
 
-    MultiShell::new(out, err, verbose)
+    let mut shell = MultiShell::new(out, err, verbose);
+    // failing to print the note is no reason not to go on
+    let _ = shell.warn_forced_color_once();
+    shell
 }
 
 pub fn handle_error(err: CliError, shell: &mut MultiShell) {
@@ -74,13 +74,11 @@ impl Compilation {
             cmd = cmd.env(k.as_slice(), v.as_ref().map(|s| s.as_slice()));
         }
 
+        for (k, v) in pkg.get_manifest().runtime_env().into_iter() {
+            cmd = cmd.env(k.as_slice(), Some(v));
+        }
+
         cmd.env("CARGO_MANIFEST_DIR", Some(pkg.get_manifest_path().dir_path()))
-           .env("CARGO_PKG_VERSION_MAJOR",
-                Some(pkg.get_version().major.to_string()))
-           .env("CARGO_PKG_VERSION_MINOR",
-                Some(pkg.get_version().minor.to_string()))
-           .env("CARGO_PKG_VERSION_PATCH",
-                Some(pkg.get_version().patch.to_string()))
            .env("CARGO_PKG_VERSION_PRE",
                 pre_version_component(pkg.get_version()))
            .cwd(pkg.get_root())
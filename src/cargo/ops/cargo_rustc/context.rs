@@ -168,7 +168,7 @@ impl<'a, 'b> Context<'a, 'b> {
         if !visiting.insert(pkg.get_package_id()) { return }
 
         let key = (pkg.get_package_id(), target.get_name());
-        let req = if target.get_profile().is_plugin() {PlatformPlugin} else {req};
+        let req = if target.for_host() {PlatformPlugin} else {req};
         match self.requirements.entry(key) {
             Occupied(mut entry) => { *entry.get_mut() = entry.get().combine(req); }
             Vacant(entry) => { entry.set(req); }
@@ -228,8 +228,8 @@ impl<'a, 'b> Context<'a, 'b> {
         if target.is_bin() || target.get_profile().is_test() {
             ret.push(format!("{}{}", stem, self.target_exe));
         } else {
-            if target.is_dylib() {
-                let plugin = target.get_profile().is_plugin();
+            if target.is_dylib() || target.is_cdylib() || target.is_proc_macro() {
+                let plugin = target.for_host();
                 let kind = if plugin {KindPlugin} else {KindTarget};
                 let (prefix, suffix) = try!(self.dylib(kind));
                 ret.push(format!("{}{}{}", prefix, stem, suffix));
@@ -5,6 +5,7 @@ use std::io::fs::PathExtensions;
 use std::os;
 
 use core::{SourceMap, Package, PackageId, PackageSet, Target, Resolve};
+use core::manifest::{Rustc, Rustdoc, Clippy};
 use util::{CargoResult, ProcessBuilder, CargoError, human, caused_human};
 use util::{Config, internal, ChainError, Fresh, profile};
 
@@ -160,12 +161,15 @@ fn compile<'a, 'b>(targets: &[&'a Target], pkg: &'a Package,
     // rebuilds on the *target* granularity, not the *package* granularity.
     let (mut libs, mut bins, mut tests) = (Vec::new(), Vec::new(), Vec::new());
     for &target in targets.iter() {
-        let work = if target.get_profile().is_doc() {
-            let (rustdoc, desc) = try!(rustdoc(pkg, target, cx));
-            vec![(rustdoc, KindTarget, desc)]
-        } else {
-            let req = cx.get_requirement(pkg, target);
-            try!(rustc(pkg, target, cx, req))
+        let work = match target.get_profile().compiler_driver() {
+            Rustdoc => {
+                let (rustdoc, desc) = try!(rustdoc(pkg, target, cx));
+                vec![(rustdoc, KindTarget, desc)]
+            }
+            Rustc | Clippy => {
+                let req = cx.get_requirement(pkg, target);
+                try!(rustc(pkg, target, cx, req))
+            }
         };
 
         let dst = match (target.is_lib(), target.get_profile().is_test()) {
@@ -273,7 +277,8 @@ fn rustc(package: &Package, target: &Target,
 fn prepare_rustc(package: &Package, target: &Target, crate_types: Vec<&str>,
                  cx: &Context, req: PlatformRequirement)
                  -> CargoResult<Vec<(ProcessBuilder, Kind)>> {
-    let base = process("rustc", package, cx);
+    let driver = target.get_profile().compiler_driver();
+    let base = process(driver.program(), package, cx);
     let base = build_base_args(cx, base, package, target, crate_types.as_slice());
 
     let target_cmd = build_plugin_args(base.clone(), cx, package, target, KindTarget);
@@ -313,6 +318,11 @@ fn rustdoc(package: &Package, target: &Target,
         None => {}
     }
 
+    for cfg in target.get_profile().get_cfgs().iter() {
+        rustdoc = rustdoc.arg("--cfg").arg(cfg.as_slice());
+    }
+    rustdoc = rustdoc.args(target.get_profile().get_cfg_rustflags().as_slice());
+
     let rustdoc = try!(build_deps_args(rustdoc, target, package, cx, kind));
 
     log!(5, "commands={}", rustdoc);
@@ -347,8 +357,6 @@ fn build_base_args(cx: &Context,
                    pkg: &Package,
                    target: &Target,
                    crate_types: &[&str]) -> ProcessBuilder {
-    let metadata = target.get_metadata();
-
     // TODO: Handle errors in converting paths into args
     cmd = cmd.arg(target.get_src_path());
 
@@ -366,25 +374,13 @@ fn build_base_args(cx: &Context,
         let root_profile = target.get_profile();
         if root_profile.get_env() != profile.get_env() { continue }
         profile = profile.opt_level(root_profile.get_opt_level())
-                         .debug(root_profile.get_debug());
-    }
-
-    if profile.get_opt_level() != 0 {
-        cmd = cmd.arg("--opt-level").arg(profile.get_opt_level().to_string());
-    }
-
-    match profile.get_codegen_units() {
-        Some(n) => cmd = cmd.arg("-C").arg(format!("codegen-units={}", n)),
-        None => {},
+                         .debug_level(root_profile.get_debug_level())
+                         .debug_assertions(root_profile.get_debug_assertions());
     }
 
-    if profile.get_debug() {
-        cmd = cmd.arg("-g");
-    } else {
-        cmd = cmd.args(["--cfg", "ndebug"]);
-    }
+    cmd = cmd.args(profile.args_for_rustc().as_slice());
 
-    if profile.is_test() && profile.uses_test_harness() {
+    if target.links_test_harness() {
         cmd = cmd.arg("--test");
     }
 
@@ -397,13 +393,7 @@ fn build_base_args(cx: &Context,
         None => {}
     }
 
-    match metadata {
-        Some(m) => {
-            cmd = cmd.arg("-C").arg(format!("metadata={}", m.metadata));
-            cmd = cmd.arg("-C").arg(format!("extra-filename={}", m.extra_filename));
-        }
-        None => {}
-    }
+    cmd = cmd.args(target.codegen_metadata_args(&profile).as_slice());
 
     return cmd;
 }
@@ -483,7 +473,7 @@ fn build_deps_args(mut cmd: ProcessBuilder, target: &Target, package: &Package,
         // target directory (hence the || here).
         let layout = cx.layout(pkg, match kind {
             KindPlugin => KindPlugin,
-            KindTarget if target.get_profile().is_plugin() => KindPlugin,
+            KindTarget if target.for_host() => KindPlugin,
             KindTarget => KindTarget,
         });
 
@@ -511,7 +501,7 @@ pub fn process<T: ToCStr>(cmd: T, pkg: &Package, cx: &Context) -> ProcessBuilder
     // or their dependencies
     let mut native_search_paths = HashSet::new();
     for &(dep, target) in cx.dep_targets(pkg).iter() {
-        if !target.get_profile().is_plugin() { continue }
+        if !target.for_host() { continue }
         each_dep(dep, cx, |dep| {
             if dep.get_manifest().get_build().len() > 0 {
                 native_search_paths.insert(layout.native(dep));
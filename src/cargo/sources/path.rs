@@ -1,7 +1,6 @@
 use std::cmp;
 use std::fmt::{mod, Show, Formatter};
 use std::io::fs::{mod, PathExtensions};
-use glob::Pattern;
 use git2;
 
 use core::{Package, PackageId, Summary, SourceId, Source, Dependency, Registry};
@@ -86,14 +85,10 @@ impl PathSource {
             _ => try!(self.list_files_walk(pkg))
         };
 
-        let pats = pkg.get_manifest().get_exclude().iter().map(|p| {
-            Pattern::new(p.as_slice())
-        }).collect::<Vec<Pattern>>();
-
+        let manifest = pkg.get_manifest();
         Ok(candidates.into_iter().filter(|candidate| {
             let relative_path = candidate.path_relative_from(&root).unwrap();
-            !pats.iter().any(|p| p.matches_path(&relative_path)) &&
-                candidate.is_file()
+            manifest.should_package(&relative_path) && candidate.is_file()
         }).collect())
     }
 
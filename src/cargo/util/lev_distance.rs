@@ -0,0 +1,29 @@
+use std::cmp;
+
+/// The Levenshtein distance between two strings: the number of single
+/// character insertions, deletions and substitutions needed to turn one into
+/// the other.
+pub fn lev_distance(me: &str, t: &str) -> uint {
+    if me.is_empty() { return t.char_len() }
+    if t.is_empty() { return me.char_len() }
+
+    let mut dcol = Vec::from_fn(t.char_len() + 1, |x| x);
+
+    for (i, sc) in me.chars().enumerate() {
+        let mut current = i;
+        *dcol.get_mut(0) = current + 1;
+
+        for (j, tc) in t.chars().enumerate() {
+            let next = dcol[j + 1];
+            if sc == tc {
+                *dcol.get_mut(j + 1) = current;
+            } else {
+                *dcol.get_mut(j + 1) = cmp::min(current, next);
+                *dcol.get_mut(j + 1) = cmp::min(dcol[j + 1], dcol[j]) + 1;
+            }
+            current = next;
+        }
+    }
+
+    dcol[t.char_len()]
+}
@@ -6,6 +6,7 @@ pub use self::errors::{CliError, FromError, ProcessError};
 pub use self::errors::{process_error, internal_error, internal, human, caused_human};
 pub use self::paths::realpath;
 pub use self::hex::{to_hex, short_hash};
+pub use self::lev_distance::lev_distance;
 pub use self::pool::TaskPool;
 pub use self::dependency_queue::{DependencyQueue, Fresh, Dirty, Freshness};
 pub use self::dependency_queue::Dependency;
@@ -14,6 +15,7 @@ pub use self::to_url::ToUrl;
 pub use self::to_semver::ToSemver;
 pub use self::vcs::{GitRepo, HgRepo};
 pub use self::sha256::Sha256;
+pub use self::term_width::term_width;
 
 pub mod graph;
 pub mod process_builder;
@@ -24,6 +26,7 @@ pub mod toml;
 pub mod paths;
 pub mod errors;
 pub mod hex;
+pub mod lev_distance;
 pub mod profile;
 pub mod to_semver;
 mod pool;
@@ -31,3 +34,4 @@ mod dependency_queue;
 mod to_url;
 mod vcs;
 mod sha256;
+mod term_width;
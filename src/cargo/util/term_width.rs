@@ -0,0 +1,49 @@
+pub use self::imp::term_width;
+
+// Asks the terminal for its size with the TIOCGWINSZ ioctl, whose request
+// number differs between platforms.
+#[cfg(unix)]
+mod imp {
+    use libc;
+
+    #[repr(C)]
+    struct WinSize {
+        ws_row: libc::c_ushort,
+        ws_col: libc::c_ushort,
+        ws_xpixel: libc::c_ushort,
+        ws_ypixel: libc::c_ushort,
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    static TIOCGWINSZ: libc::c_ulong = 0x5413;
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd",
+              target_os = "dragonfly"))]
+    static TIOCGWINSZ: libc::c_ulong = 0x40087468;
+
+    extern {
+        fn ioctl(fd: libc::c_int, request: libc::c_ulong, ...) -> libc::c_int;
+    }
+
+    /// The number of columns of the terminal `fd` refers to, or `None` if it
+    /// isn't a terminal.
+    pub fn term_width(fd: libc::c_int) -> Option<uint> {
+        let mut size = WinSize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+        let ret = unsafe { ioctl(fd, TIOCGWINSZ, &mut size as *mut WinSize) };
+        if ret == 0 && size.ws_col > 0 {
+            Some(size.ws_col as uint)
+        } else {
+            None
+        }
+    }
+}
+
+// The console size on windows needs a handle to the console rather than a
+// file descriptor, so the width isn't detected there yet.
+#[cfg(windows)]
+mod imp {
+    use libc;
+
+    pub fn term_width(_fd: libc::c_int) -> Option<uint> {
+        None
+    }
+}
@@ -10,10 +10,10 @@ use semver;
 use serialize::{Decodable, Decoder};
 
 use core::{SourceId, GitKind};
-use core::manifest::{LibKind, Lib, Dylib, Profile};
+use core::manifest::{LibKind, Lib, Dylib, Profile, OptLevel, Deprecation};
 use core::{Summary, Manifest, Target, Dependency, PackageId};
 use core::package_id::Metadata;
-use util::{CargoResult, Require, human, ToUrl, ToSemver};
+use util::{CargoResult, CargoError, Require, human, ToUrl, ToSemver};
 
 /// Representation of the projects file layout.
 ///
@@ -224,9 +224,34 @@ pub struct TomlProfiles {
 
 #[deriving(Decodable, Clone, Default)]
 pub struct TomlProfile {
-    opt_level: Option<uint>,
+    opt_level: Option<TomlOptLevel>,
     codegen_units: Option<uint>,
     debug: Option<bool>,
+    debug_assertions: Option<bool>,
+}
+
+#[deriving(Clone)]
+pub struct TomlOptLevel(OptLevel);
+
+// `opt-level` is a number, or a string for the `s` and `z` levels
+#[deriving(Decodable)]
+enum TomlOptLevelValue {
+    OptLevelNum(uint),
+    OptLevelStr(String),
+}
+
+impl<E, D: Decoder<E>> Decodable<D, E> for TomlOptLevel {
+    fn decode(d: &mut D) -> Result<TomlOptLevel, E> {
+        let value: TomlOptLevelValue = raw_try!(Decodable::decode(d));
+        let s = match value {
+            OptLevelNum(n) => n.to_string(),
+            OptLevelStr(s) => s,
+        };
+        match OptLevel::from_str(s.as_slice()) {
+            Ok(level) => Ok(TomlOptLevel(level)),
+            Err(e) => Err(d.error(e.description().as_slice())),
+        }
+    }
 }
 
 #[deriving(Decodable)]
@@ -249,8 +274,10 @@ pub struct TomlProject {
     name: String,
     version: TomlVersion,
     pub authors: Vec<String>,
+    description: Option<String>,
     build: Option<TomlBuildCommandsList>,
     exclude: Option<Vec<String>>,
+    include: Option<Vec<String>>,
 }
 
 #[deriving(Decodable)]
@@ -477,10 +504,15 @@ impl TomlManifest {
                                          layout.root.join("doc"),
                                          sources,
                                          build,
-                                         exclude);
+                                         exclude,
+                                         HashMap::new());
+        manifest.set_authors(project.authors.clone());
+        manifest.set_description(project.description.clone());
+        manifest.set_include(project.include.clone().unwrap_or(Vec::new()));
         if used_deprecated_lib {
-            manifest.add_warning(format!("the [[lib]] section has been \
-                                          deprecated in favor of [lib]"));
+            manifest.add_warning_kind(format!("the [[lib]] section has been \
+                                               deprecated in favor of [lib]"),
+                                      Deprecation);
         }
         Ok((manifest, nested_paths))
     }
@@ -610,10 +642,18 @@ fn normalize(libs: &[TomlLibTarget],
             Some(ref toml) => toml,
             None => return profile,
         };
-        let opt_level = toml.opt_level.unwrap_or(profile.get_opt_level());
+        let opt_level = match toml.opt_level {
+            Some(TomlOptLevel(level)) => level,
+            None => profile.get_opt_level(),
+        };
         let codegen_units = toml.codegen_units;
         let debug = toml.debug.unwrap_or(profile.get_debug());
+        // `debug` used to control assertions as well, so it still does unless
+        // they're configured separately
+        let debug_assertions = toml.debug_assertions.or(toml.debug)
+                                   .unwrap_or(profile.get_debug_assertions());
         profile.opt_level(opt_level).codegen_units(codegen_units).debug(debug)
+               .debug_assertions(debug_assertions)
     }
 
     fn target_profiles(target: &TomlTarget, profiles: &TomlProfiles,
@@ -120,6 +120,10 @@ impl ProjectBuilder {
         process(program)
             .cwd(self.root())
             .env("HOME", Some(paths::home().display().to_string().as_slice()))
+            // keep the output free of color escapes, whatever the environment
+            .env("CI", None::<&str>)
+            .env("GITHUB_ACTIONS", None::<&str>)
+            .env("TERM", None::<&str>)
     }
 
     pub fn cargo_process(&self, cmd: &str) -> ProcessBuilder {
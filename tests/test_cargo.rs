@@ -6,6 +6,8 @@ use cargo::util::process;
 
 use support::paths;
 use support::{project, cargo_dir, mkdir_recursive, ProjectBuilder, ResultTest};
+use support::{basic_bin_manifest, main_file};
+use term::{Terminal, TerminfoTerminal};
 
 fn setup() {
 }
@@ -44,3 +46,22 @@ test!(list_commands_looks_at_path {
     let output = str::from_utf8(output.output.as_slice()).assert();
     assert!(output.contains("\n    1\n"), "missing 1: {}", output);
 })
+
+test!(forced_color_note {
+    let term: Option<TerminfoTerminal<io::MemWriter>> =
+        Terminal::new(io::MemWriter::new());
+    if term.is_none() { return }
+
+    let p = project("foo")
+        .file("Cargo.toml", basic_bin_manifest("foo").as_slice())
+        .file("src/foo.rs", main_file(r#""i am foo""#, []).as_slice());
+
+    // the test's output is piped, so a CI environment should print the note once
+    let output = p.cargo_process("build")
+                  .env("GITHUB_ACTIONS", Some("true"))
+                  .env("TERM", os::getenv("TERM"))
+                  .exec_with_output().assert();
+    let err = str::from_utf8(output.error.as_slice()).assert();
+    let note = "note: color output was forced, but output is not a terminal";
+    assert_eq!(err.split_str(note).count(), 2);
+})
@@ -1133,6 +1133,7 @@ test!(verbose_build {
                 execs().with_status(0).with_stdout(format!("\
 {compiling} test v0.0.0 ({url})
 {running} `rustc {dir}{sep}src{sep}lib.rs --crate-name test --crate-type lib -g \
+        -C debug-assertions=on \
         -C metadata=[..] \
         -C extra-filename=-[..] \
         --out-dir {dir}{sep}target \
@@ -1163,6 +1164,7 @@ test!(verbose_release_build {
 {running} `rustc {dir}{sep}src{sep}lib.rs --crate-name test --crate-type lib \
         --opt-level 3 \
         --cfg ndebug \
+        -C debug-assertions=off \
         -C metadata=[..] \
         -C extra-filename=-[..] \
         --out-dir {dir}{sep}target{sep}release \
@@ -1209,6 +1211,7 @@ test!(verbose_release_build_deps {
         --crate-type dylib --crate-type rlib \
         --opt-level 3 \
         --cfg ndebug \
+        -C debug-assertions=off \
         -C metadata=[..] \
         -C extra-filename=-[..] \
         --out-dir {dir}{sep}target{sep}release{sep}deps \
@@ -1219,6 +1222,7 @@ test!(verbose_release_build_deps {
 {running} `rustc {dir}{sep}src{sep}lib.rs --crate-name test --crate-type lib \
         --opt-level 3 \
         --cfg ndebug \
+        -C debug-assertions=off \
         -C metadata=[..] \
         -C extra-filename=-[..] \
         --out-dir {dir}{sep}target{sep}release \
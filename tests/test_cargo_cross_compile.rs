@@ -296,6 +296,7 @@ test!(linker_and_ar {
                        .with_stdout(format!("\
 {compiling} foo v0.5.0 ({url})
 {running} `rustc src/foo.rs --crate-name foo --crate-type bin -g \
+    -C debug-assertions=on \
     --out-dir {dir}{sep}target{sep}{target} \
     --dep-info [..] \
     --target {target} \
@@ -29,6 +29,7 @@ test!(profile_overrides {
 {running} `rustc {dir}{sep}src{sep}lib.rs --crate-name test --crate-type lib \
         --opt-level 1 \
         --cfg ndebug \
+        -C debug-assertions=off \
         -C metadata=[..] \
         -C extra-filename=-[..] \
         --out-dir {dir}{sep}target \
@@ -42,6 +43,62 @@ url = p.url(),
 )));
 })
 
+test!(opt_level_for_size {
+    let mut p = project("foo");
+    p = p
+        .file("Cargo.toml", r#"
+            [package]
+
+            name = "test"
+            version = "0.0.0"
+            authors = []
+
+            [profile.dev]
+            opt-level = "s"
+            debug = false
+        "#)
+        .file("src/lib.rs", "");
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0).with_stdout(format!("\
+{compiling} test v0.0.0 ({url})
+{running} `rustc {dir}{sep}src{sep}lib.rs --crate-name test --crate-type lib \
+        -C opt-level=s \
+        --cfg ndebug \
+        -C debug-assertions=off \
+        -C metadata=[..] \
+        -C extra-filename=-[..] \
+        --out-dir {dir}{sep}target \
+        --dep-info [..] \
+        -L {dir}{sep}target \
+        -L {dir}{sep}target{sep}deps`
+",
+running = RUNNING, compiling = COMPILING, sep = path::SEP,
+dir = p.root().display(),
+url = p.url(),
+)));
+})
+
+test!(invalid_opt_level {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+
+            name = "test"
+            version = "0.0.0"
+            authors = []
+
+            [profile.dev]
+            opt-level = 7
+        "#)
+        .file("src/lib.rs", "");
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+Cargo.toml is not a valid manifest
+
+`7` is not a valid opt level, expected 0, 1, 2, 3, s or z[..]
+"));
+})
+
 test!(top_level_overrides_deps {
     let mut p = project("foo");
     p = p
@@ -83,6 +140,7 @@ test!(top_level_overrides_deps {
         --crate-type dylib --crate-type rlib \
         --opt-level 1 \
         -g \
+        -C debug-assertions=on \
         -C metadata=[..] \
         -C extra-filename=-[..] \
         --out-dir {dir}{sep}target{sep}release{sep}deps \
@@ -93,6 +151,7 @@ test!(top_level_overrides_deps {
 {running} `rustc {dir}{sep}src{sep}lib.rs --crate-name test --crate-type lib \
         --opt-level 1 \
         -g \
+        -C debug-assertions=on \
         -C metadata=[..] \
         -C extra-filename=-[..] \
         --out-dir {dir}{sep}target{sep}release \
@@ -1,8 +1,13 @@
 use support::{ResultTest,Tap,shell_writes};
 use hamcrest::{assert_that};
-use std::io::{MemWriter, BufWriter, IoResult};
-use cargo::core::shell::{Shell,ShellConfig};
+use std::collections::HashMap;
+use std::io::{MemWriter, BufReader, BufWriter, IoResult};
+use cargo::core::shell::{Shell,MultiShell,ShellConfig,ColorConfig,Auto,Always,Never,Json};
+use serialize::json;
+use cargo::core::{Manifest, PackageId, Profile, RegistryKind, SourceId, Summary, Target};
+use cargo::util::{CargoError, ToUrl, human};
 use term::{Terminal,TerminfoTerminal,color};
+use semver::Version;
 
 fn setup() {
 }
@@ -11,8 +16,24 @@ fn writer(buf: &mut [u8]) -> Box<Writer> {
     box BufWriter::new(buf) as Box<Writer>
 }
 
+fn multi_shell<'a>(out: &'a mut [u8], err: &'a mut [u8]) -> MultiShell<'a> {
+    let config = ShellConfig { color: Never, verbose: true, tty: false,
+                               width: None, bell: false };
+    MultiShell::new(Shell::create(writer(out), config),
+                    Shell::create(writer(err), config),
+                    true)
+}
+
+fn written(buf: &[u8]) -> &[u8] {
+    match buf.iter().position(|a| *a == 0) {
+        Some(pos) => buf.slice_to(pos),
+        None => buf
+    }
+}
+
 test!(non_tty {
-    let config = ShellConfig { color: true, verbose: true, tty: false };
+    let config = ShellConfig { color: Auto, verbose: true, tty: false,
+                               width: None, bell: false };
     let mut buf: Vec<u8> = Vec::from_elem(9, 0 as u8);
 
     Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
@@ -22,7 +43,8 @@ test!(non_tty {
 })
 
 test!(color_explicitly_disabled {
-    let config = ShellConfig { color: false, verbose: true, tty: true };
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
     let mut buf: Vec<u8> = Vec::from_elem(9, 0 as u8);
 
     Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
@@ -36,7 +58,8 @@ test!(colored_shell {
         Terminal::new(MemWriter::new());
     if term.is_none() { return }
 
-    let config = ShellConfig { color: true, verbose: true, tty: true };
+    let config = ShellConfig { color: Auto, verbose: true, tty: true,
+                               width: None, bell: false };
     let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
 
     Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
@@ -57,3 +80,774 @@ fn colored_output<S: Str>(string: S, color: color::Color) -> IoResult<String> {
     try!(term.flush());
     Ok(String::from_utf8_lossy(term.get_ref().get_ref()).to_string())
 }
+
+// Runs `f` against a verbose shell with the given settings, returning
+// everything it wrote.
+fn shell_with(color: ColorConfig, tty: bool, width: Option<uint>,
+              f: |&mut Shell|) -> Vec<u8> {
+    let config = ShellConfig { color: color, verbose: true, tty: tty,
+                               width: width, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(200, 0 as u8);
+    {
+        let mut shell = Shell::create(writer(buf.as_mut_slice()), config);
+        f(&mut shell);
+    }
+    written(buf.as_slice()).to_vec()
+}
+
+test!(colored_diff {
+    let term: Option<TerminfoTerminal<MemWriter>> =
+        Terminal::new(MemWriter::new());
+    if term.is_none() { return }
+
+    let config = ShellConfig { color: Auto, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_diff("-old\n+new").assert();
+        let expected = format!("{}{}",
+                               colored_output("-old\n", color::RED).assert(),
+                               colored_output("+new\n", color::GREEN).assert());
+        assert_that(written(buf.as_slice()), shell_writes(expected));
+    });
+})
+
+test!(diff_no_color {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_diff("@@ -1 +1 @@\n-old\n+new\n same").assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("@@ -1 +1 @@\n-old\n+new\n same\n"));
+    });
+})
+
+test!(say_percent {
+    let out = shell_with(Never, true, None, |shell| {
+        shell.say_percent(1, 3, "a").assert();
+        shell.say_percent(2, 3, "b").assert();
+        shell.say_percent(0, 0, "c").assert();
+    });
+    assert_that(out.as_slice(), shell_writes("33% a\r67% b\r100% c\r"));
+})
+
+test!(say_percent_cleared {
+    let out = shell_with(Never, true, None, |shell| {
+        shell.say_percent(1, 2, "abc").assert();
+        shell.say_percent(2, 2, "d").assert();
+        shell.say_status("Finished", "foo", color::GREEN).assert();
+    });
+    assert_that(out.as_slice(),
+                shell_writes("50% abc\r100% d \r       \r    Finished foo\n"));
+})
+
+test!(say_percent_non_tty {
+    let out = shell_with(Never, false, None, |shell| {
+        shell.say_percent(1, 2, "a").assert();
+    });
+    assert_that(out.as_slice(), shell_writes(""));
+})
+
+test!(progress_bar {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(200, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.progress(1, 4, "a").assert();
+        shell.say_status("Compiling", "foo", color::GREEN).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes(format!("[=====               ] 1/4 a\r\
+                                          {}\r   Compiling foo\n",
+                                         String::from_char(28, ' '))));
+    });
+})
+
+test!(progress_bar_non_tty {
+    let config = ShellConfig { color: Never, verbose: true, tty: false,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.progress(1, 4, "a").assert();
+        shell.clear_progress().assert();
+        shell.say_status("Compiling", "foo", color::GREEN).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("   Compiling foo\n"));
+    });
+})
+
+test!(version_bump_no_color {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let from = Version::parse("1.2.3").unwrap();
+    let to = Version::parse("1.3.0").unwrap();
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_version_bump("foo", &from, &to).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("foo: 1.2.3 -> 1.3.0\n"));
+    });
+})
+
+test!(upgrade_available {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let current = Version::parse("1.2.3").unwrap();
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_upgrade_available("foo", &current, &current).assert();
+        shell.say_upgrade_available("foo", &current,
+                                    &Version::parse("1.0.0").unwrap()).assert();
+        assert_that(written(buf.as_slice()), shell_writes(""));
+
+        shell.say_upgrade_available("foo", &current,
+                                    &Version::parse("1.10.0").unwrap()).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("foo 1.10.0 is available (currently 1.2.3)\n"));
+    });
+})
+
+test!(version_bump_colors_minor {
+    let term: Option<TerminfoTerminal<MemWriter>> =
+        Terminal::new(MemWriter::new());
+    if term.is_none() { return }
+
+    let config = ShellConfig { color: Auto, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let from = Version::parse("1.2.3").unwrap();
+    let to = Version::parse("1.3.0").unwrap();
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_version_bump("foo", &from, &to).assert();
+
+        let mut term: TerminfoTerminal<MemWriter> =
+            Terminal::new(MemWriter::new()).assert();
+        term.reset().assert();
+        term.write_str("foo: 1.2.3 -> 1.").assert();
+        term.fg(color::GREEN).assert();
+        term.write_str("3").assert();
+        term.reset().assert();
+        term.write_str(".0").assert();
+        term.reset().assert();
+        term.write_str("\n").assert();
+        let expected = String::from_utf8_lossy(term.get_ref().get_ref()).to_string();
+        assert_that(written(buf.as_slice()), shell_writes(expected));
+    });
+})
+
+test!(terminal_progress {
+    let out = shell_with(Never, true, None, |shell| {
+        shell.set_terminal_progress(42).assert();
+        shell.clear_terminal_progress().assert();
+    });
+    assert_that(out.as_slice(), shell_writes("\x1b]9;4;1;42\x07\x1b]9;4;0\x07"));
+})
+
+test!(terminal_progress_non_tty {
+    let out = shell_with(Never, false, None, |shell| {
+        shell.set_terminal_progress(42).assert();
+        shell.clear_terminal_progress().assert();
+    });
+    assert_that(out.as_slice(), shell_writes(""));
+})
+
+test!(say_rule {
+    let out = shell_with(Never, true, Some(10), |shell| {
+        shell.say_rule('=').assert();
+    });
+    assert_that(out.as_slice(), shell_writes("==========\n"));
+
+    let out = shell_with(Never, false, None, |shell| {
+        shell.say_rule('-').assert();
+    });
+    assert_eq!(out.len(), 41);
+})
+
+test!(select_from_input {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(200, 0 as u8);
+    let options = vec!["foo".to_string(), "bar".to_string()];
+
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        let mut input = BufReader::new(b"2\n");
+        assert_eq!(shell.select_from("Which binary?", options.as_slice(),
+                                     &mut input).assert(), 1);
+    }
+
+    assert_that(written(err.as_slice()),
+                shell_writes("Which binary?\n  1) foo\n  2) bar\n"));
+})
+
+test!(select_reprompts_on_bad_input {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(200, 0 as u8);
+    let options = vec!["foo".to_string(), "bar".to_string()];
+
+    let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+    let mut input = BufReader::new(b"3\nfoo\n1\n");
+    assert_eq!(shell.select_from("Which binary?", options.as_slice(),
+                                 &mut input).assert(), 0);
+})
+
+test!(select_requires_tty {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let options = vec!["foo".to_string()];
+
+    let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+    assert!(shell.select("Which binary?", options.as_slice()).is_err());
+})
+
+test!(say_highlighted_no_color {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_highlighted("foo bar baz", [(0, 3), (8, 11)]).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes(">>foo<< bar >>baz<<\n"));
+    });
+})
+
+test!(say_highlighted_invalid_ranges {
+    // unsorted, splitting `é`, overlapping and past the end of the message
+    let out = shell_with(Never, true, None, |shell| {
+        shell.say_highlighted("héllo wörld", [(7, 13), (0, 1), (2, 4), (12, 40),
+                                              (50, 60)]).assert();
+    });
+    assert_that(out.as_slice(), shell_writes(">>h<<éllo >>wörld<<\n"));
+})
+
+test!(format_bytes {
+    assert_eq!(Shell::format_bytes(512).as_slice(), "512 B");
+    assert_eq!(Shell::format_bytes(1536).as_slice(), "1.5 KiB");
+    assert_eq!(Shell::format_bytes(3 * 1024 * 1024 + 400 * 1024).as_slice(),
+               "3.4 MiB");
+})
+
+test!(alert {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: true };
+    let mut buf: Vec<u8> = Vec::from_elem(10, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.alert().assert();
+        assert_that(written(buf.as_slice()), shell_writes("\x07"));
+    });
+
+    let config = ShellConfig { color: Never, verbose: true, tty: false,
+                               width: None, bell: true };
+    let mut buf: Vec<u8> = Vec::from_elem(10, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.alert().assert();
+        assert_that(written(buf.as_slice()), shell_writes(""));
+    });
+
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(10, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.alert().assert();
+        assert_that(written(buf.as_slice()), shell_writes(""));
+    });
+})
+
+test!(no_forced_color_note_when_color_disabled {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    {
+        // neither stream is colored, so there is nothing to note
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        assert!(!shell.err().is_color_forced());
+        shell.warn_forced_color_once().assert();
+    }
+
+    assert_that(written(err.as_slice()), shell_writes(""));
+})
+
+test!(version_req {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_version_req("foo", ">= 1.2.3, < 2.0.0").assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("foo >= 1.2.3, < 2.0.0\n"));
+    });
+
+    let term: Option<TerminfoTerminal<MemWriter>> =
+        Terminal::new(MemWriter::new());
+    if term.is_none() { return }
+
+    let config = ShellConfig { color: Auto, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_version_req("foo", "^1.2.3").assert();
+        let output = String::from_utf8_lossy(written(buf.as_slice())).to_string();
+        assert!(output.as_slice().contains("foo "));
+        assert!(output.as_slice().contains("^"));
+        assert!(output.as_slice().contains("1.2.3"));
+    });
+})
+
+test!(numbered_causes {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let error = human("failed to compile").with_cause(
+        human("failed to fetch").with_cause(human("network down")));
+
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.print_numbered_causes(&error).assert();
+    }
+
+    assert_that(written(err.as_slice()),
+                shell_writes("failed to compile\n  \
+                              1: failed to fetch\n  \
+                              2: network down\n"));
+})
+
+test!(non_tty_ci_environment {
+    let term: Option<TerminfoTerminal<MemWriter>> =
+        Terminal::new(MemWriter::new());
+    if term.is_none() { return }
+
+    let config = ShellConfig { color: Auto, verbose: true, tty: false,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let github = |key: &str| {
+        if key == "GITHUB_ACTIONS" {Some("true".to_string())} else {None}
+    };
+
+    Shell::create_with_env(writer(buf.as_mut_slice()), config, github).tap(|shell| {
+        assert!(shell.is_color_forced());
+    });
+
+    let dumb = |key: &str| {
+        match key {
+            "GITHUB_ACTIONS" => Some("true".to_string()),
+            "TERM" => Some("dumb".to_string()),
+            _ => None,
+        }
+    };
+    Shell::create_with_env(writer(buf.as_mut_slice()), config, dumb).tap(|shell| {
+        assert!(!shell.is_color_forced());
+    });
+})
+
+test!(color_always_when_piped {
+    let term: Option<TerminfoTerminal<MemWriter>> =
+        Terminal::new(MemWriter::new());
+    if term.is_none() { return }
+
+    let always = ShellConfig { color: Always, verbose: true, tty: false,
+                               width: None, bell: false };
+    let auto = ShellConfig { color: Auto, verbose: true, tty: false,
+                             width: None, bell: false };
+    let never = ShellConfig { color: Never, verbose: true, tty: true,
+                              width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), always).tap(|shell| {
+        assert!(shell.is_color_forced());
+    });
+    Shell::create(writer(buf.as_mut_slice()), auto).tap(|shell| {
+        assert!(!shell.is_color_forced());
+    });
+    Shell::create(writer(buf.as_mut_slice()), never).tap(|shell| {
+        shell.say("Hey Alex", color::RED).assert();
+        assert_that(written(buf.as_slice()), shell_writes("Hey Alex\n"));
+    });
+})
+
+test!(forced_color_note_printed_once {
+    let term: Option<TerminfoTerminal<MemWriter>> =
+        Terminal::new(MemWriter::new());
+    if term.is_none() { return }
+
+    let forced = ShellConfig { color: Always, verbose: true, tty: false,
+                               width: None, bell: false };
+    let plain = ShellConfig { color: Never, verbose: true, tty: false,
+                              width: None, bell: false };
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(200, 0 as u8);
+
+    {
+        let out = Shell::create(writer(out.as_mut_slice()), forced);
+        let err = Shell::create(writer(err.as_mut_slice()), plain);
+        let mut shell = MultiShell::new(out, err, true);
+        shell.warn_forced_color_once().assert();
+        shell.warn_forced_color_once().assert();
+    }
+
+    assert_that(written(err.as_slice()),
+                shell_writes("note: color output was forced, but output is not a \
+                              terminal\n"));
+})
+
+test!(say_manifest {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    let source = SourceId::new(RegistryKind, "http://example.com".to_url().unwrap());
+    let pkg_id = PackageId::new("foo", "1.2.3", &source).unwrap();
+    let summary = Summary::new(pkg_id, Vec::new(), HashMap::new()).unwrap();
+    let profile = Profile::default_dev();
+    let targets = vec![
+        Target::bin_target("foo", &Path::new("src/main.rs"), &profile, None),
+        Target::bin_target("bar", &Path::new("src/bin/bar.rs"), &profile, None),
+    ];
+    let manifest = Manifest::new(summary, targets, Path::new("target"),
+                                 Path::new("doc"), Vec::new(), Vec::new(),
+                                 Vec::new(), HashMap::new());
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_manifest(&manifest).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("foo v1.2.3 (2 targets)\n"));
+    });
+})
+
+test!(say_result_no_color {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_result(true, "foo::bar").assert();
+        shell.say_result(false, "foo::baz").assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("[ok] foo::bar\n[FAIL] foo::baz\n"));
+    });
+})
+
+test!(say_features_no_color {
+    let features = [("default".to_string(), true),
+                    ("serde".to_string(), false),
+                    ("std".to_string(), true)];
+    let out = shell_with(Never, true, None, |shell| {
+        shell.say_features(features.as_slice()).assert();
+    });
+    assert_eq!(out.iter().filter(|&&b| b == b'\n').count(), 3);
+    assert_that(out.as_slice(), shell_writes("+ default\n- serde\n+ std\n"));
+})
+
+test!(grouped_warnings {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(200, 0 as u8);
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        let warnings = [
+            ("unused".to_string(), "variable `a`".to_string()),
+            ("dead_code".to_string(), "function `b`".to_string()),
+            ("unused".to_string(), "import `c`".to_string()),
+        ];
+        shell.print_grouped_warnings(warnings.as_slice()).assert();
+    }
+
+    assert_that(written(err.as_slice()),
+                shell_writes("warning: unused\n    variable `a`\n    import `c`\n\
+                              warning: dead_code\n    function `b`\n"));
+})
+
+test!(print_conflict {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.print_conflict("bar", "^1.2.0", "=2.0.1").assert();
+    }
+
+    let output = String::from_utf8_lossy(written(err.as_slice())).to_string();
+    assert!(output.as_slice().contains("^1.2.0"));
+    assert!(output.as_slice().contains("=2.0.1"));
+    assert_that(written(err.as_slice()),
+                shell_writes("bar required at both ^1.2.0 and =2.0.1\n"));
+})
+
+test!(emit_timing {
+    let mut out: Vec<u8> = Vec::from_elem(200, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.emit_timing("foo", "codegen", 1.5).assert();
+    }
+    assert_that(written(out.as_slice()), shell_writes(""));
+
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.set_message_format(Json);
+        shell.emit_timing("foo", "codegen", 1.5).assert();
+    }
+    let output = String::from_utf8(written(out.as_slice()).to_vec()).unwrap();
+    assert!(output.as_slice().ends_with("\n"));
+    let event = json::from_str(output.as_slice().trim()).unwrap();
+    let field = |name: &str| event.find(&name.to_string()).unwrap();
+    assert_eq!(field("reason").as_string(), Some("timing-info"));
+    assert_eq!(field("target").as_string(), Some("foo"));
+    assert_eq!(field("phase").as_string(), Some("codegen"));
+    assert_eq!(field("duration").as_f64(), Some(1.5));
+})
+
+test!(wrap {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: Some(24), bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(200, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        assert_eq!(shell.wrap("short").as_slice(), "short");
+        assert_eq!(shell.wrap("one two three four five six").as_slice(),
+                   "one two three four five\n             six");
+        assert_eq!(shell.wrap("first line\nsecond line which is long").as_slice(),
+                   "first line\nsecond line which is\n             long");
+
+        shell.say_status("Compiling", "foo v0.1.0 (file:///foo)", color::GREEN).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("   Compiling foo v0.1.0\n             (file:///foo)\n"));
+    });
+
+    let config = ShellConfig { color: Never, verbose: true, tty: false,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(10, 0 as u8);
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        assert_eq!(shell.wrap("one two three four five six").as_slice(),
+                   "one two three four five six");
+    });
+})
+
+test!(say_status_with_width {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(200, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_status_with("Documenting", "foo", color::CYAN, 16).assert();
+        shell.say_status_with("Uninstalling", "foo", color::RED, 12).assert();
+        shell.say_status_with("Post-processing", "foo", color::GREEN, 12).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("     Documenting foo\n\
+                                  Uninstalling foo\n\
+                                  Post-processing foo\n"));
+    });
+})
+
+test!(wrap_wide_status {
+    let out = shell_with(Never, true, Some(24), |shell| {
+        shell.say_status_with("Documenting", "foo bar baz", color::CYAN, 16).assert();
+    });
+    assert_that(out.as_slice(),
+                shell_writes("     Documenting foo bar\n                 baz\n"));
+})
+
+test!(status_with_color {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.status_with_color("Downloading", "foo v0.1.0", color::CYAN).assert();
+        shell.status("Compiling", "foo v0.1.0").assert();
+    }
+
+    assert_that(written(out.as_slice()),
+                shell_writes(" Downloading foo v0.1.0\n   Compiling foo v0.1.0\n"));
+})
+
+test!(wrap_keeps_whitespace {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: Some(24), bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(200, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        assert_eq!(shell.wrap("    indented  by   spaces").as_slice(),
+                   "    indented  by   spaces");
+        assert_eq!(shell.wrap("  one  two three four five").as_slice(),
+                   "  one  two three four\n             five");
+
+        shell.say("-a line which is longer than the width", color::BLACK).assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("-a line which is longer than the width\n"));
+    });
+})
+
+test!(quiet {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.set_quiet(true);
+        shell.status("Compiling", "foo v0.1.0").assert();
+        shell.say("hello", color::BLACK).assert();
+        shell.warn("careful").assert();
+        shell.error("failed").assert();
+    }
+
+    assert_that(written(out.as_slice()), shell_writes(""));
+    assert_that(written(err.as_slice()), shell_writes("careful\nfailed\n"));
+})
+
+test!(warn_with_code_no_color {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.warn_with_code("unused_variables", "unused variable: `x`").assert();
+    }
+
+    assert_that(written(out.as_slice()), shell_writes(""));
+    assert_that(written(err.as_slice()),
+                shell_writes("warning[unused_variables]: unused variable: `x`\n"));
+})
+
+test!(note {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.note("using cached index").assert();
+    }
+
+    assert_that(written(out.as_slice()), shell_writes(""));
+    assert_that(written(err.as_slice()),
+                shell_writes("note: using cached index\n"));
+})
+
+test!(note_quiet {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.set_quiet(true);
+        shell.note("using cached index").assert();
+    }
+
+    assert_that(written(out.as_slice()), shell_writes(""));
+    assert_that(written(err.as_slice()), shell_writes(""));
+})
+
+test!(suggest_no_color {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.suggest("`--features` takes a list, try:", "cargo build --features foo").assert();
+    }
+
+    assert_that(written(out.as_slice()), shell_writes(""));
+    assert_that(written(err.as_slice()),
+                shell_writes("`--features` takes a list, try:\n    \
+                              cargo build --features foo\n"));
+})
+
+test!(suggest_colored {
+    let term: Option<TerminfoTerminal<MemWriter>> =
+        Terminal::new(MemWriter::new());
+    if term.is_none() { return }
+
+    let config = ShellConfig { color: Auto, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(200, 0 as u8);
+
+    {
+        let mut shell = MultiShell::new(Shell::create(writer(out.as_mut_slice()), config),
+                                        Shell::create(writer(err.as_mut_slice()), config),
+                                        true);
+        shell.suggest("try:", "cargo build").assert();
+    }
+
+    let err = String::from_utf8_lossy(written(err.as_slice())).to_string();
+    assert!(err.as_slice().contains("try:"));
+    let pos = err.as_slice().find_str("cargo build").unwrap();
+    assert!(err.as_slice().slice_to(pos).ends_with("m"),
+            "command not styled: {}", err);
+    assert!(!err.as_slice().contains("    cargo build"));
+})
+
+test!(tree_lines {
+    let config = ShellConfig { color: Never, verbose: true, tty: false,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(200, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_tree_line(0, true, "foo").assert();
+        shell.say_tree_line(1, false, "bar").assert();
+        shell.say_tree_line(2, true, "baz").assert();
+        shell.say_tree_line(1, true, "qux").assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("foo\n\
+                                  ├── bar\n\
+                                  │   └── baz\n\
+                                  └── qux\n"));
+    });
+})
+
+test!(deprecated_once {
+    let mut out: Vec<u8> = Vec::from_elem(100, 0 as u8);
+    let mut err: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    {
+        let mut shell = multi_shell(out.as_mut_slice(), err.as_mut_slice());
+        shell.deprecated_once("lib", "[[lib]] is deprecated").assert();
+        shell.deprecated_once("lib", "[[lib]] is deprecated").assert();
+    }
+
+    assert_that(written(err.as_slice()),
+                shell_writes("[[lib]] is deprecated\n"));
+})
+
+test!(say_inline {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_inline("Compiling 1/2").assert();
+        assert_that(written(buf.as_slice()), shell_writes("Compiling 1/2\r"));
+    });
+
+    let config = ShellConfig { color: Never, verbose: true, tty: false,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.say_inline("Compiling 1/2").assert();
+        assert_that(written(buf.as_slice()), shell_writes("Compiling 1/2\n"));
+    });
+})
+
+test!(ensure_newline {
+    let config = ShellConfig { color: Never, verbose: true, tty: true,
+                               width: None, bell: false };
+    let mut buf: Vec<u8> = Vec::from_elem(100, 0 as u8);
+
+    Shell::create(writer(buf.as_mut_slice()), config).tap(|shell| {
+        shell.ensure_newline().assert();
+        shell.say_inline("Compiling 1/2").assert();
+        shell.ensure_newline().assert();
+        shell.ensure_newline().assert();
+        shell.say("done", color::BLACK).assert();
+        shell.ensure_newline().assert();
+        assert_that(written(buf.as_slice()),
+                    shell_writes("Compiling 1/2\r\ndone\n"));
+    });
+})
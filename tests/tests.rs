@@ -5,6 +5,7 @@ extern crate cargo;
 extern crate flate2;
 extern crate git2;
 extern crate hamcrest;
+extern crate semver;
 extern crate serialize;
 extern crate tar;
 extern crate term;